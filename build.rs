@@ -13,6 +13,9 @@ fn main() {
         .warnings(false)
         .flag_if_supported("-Wno-unused-result")
         .flag_if_supported("-Wno-format-overflow")
+        // route fatal errors to api.c instead of exiting the process
+        .define("exit", Some("espresso_rs_exit"))
+        .define("fatal", Some("espresso_rs_fatal"))
         .file("espresso-logic/espresso-src/black_white.c")
         .file("espresso-logic/espresso-src/canonical.c")
         .file("espresso-logic/espresso-src/cofactor.c")
@@ -23,7 +26,7 @@ fn main() {
         .file("espresso-logic/espresso-src/cubestr.c")
         .file("espresso-logic/espresso-src/cvrin.c")
         .file("espresso-logic/espresso-src/cvrm.c")
-        .file("espresso-logic/espresso-src/cvrmisc.c")
+        .file("src/fatal.c")
         .file("espresso-logic/espresso-src/cvrout.c")
         .file("espresso-logic/espresso-src/dominate.c")
         .file("espresso-logic/espresso-src/equiv.c")
@@ -47,7 +50,7 @@ fn main() {
         .file("espresso-logic/espresso-src/part.c")
         .file("espresso-logic/espresso-src/primes.c")
         .file("espresso-logic/espresso-src/prtime.c")
-        .file("espresso-logic/espresso-src/reduce.c")
+        .file("src/reduce_toggle.c")
         .file("espresso-logic/espresso-src/rows.c")
         .file("espresso-logic/espresso-src/set.c")
         .file("espresso-logic/espresso-src/setc.c")
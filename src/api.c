@@ -2,85 +2,257 @@
   Entry point of the espresso source code.
   Replaces main.c
  */
+#include <setjmp.h>
 #include <stdio.h>
 #include <string.h>
 #include <stdlib.h>
 #include "espresso.h"
+#include "signature.h"
 
-// Runs plan espresso with no flags
-char * run_espresso(FILE * fpla, FILE *outfile) {
-  pPLA PLA;
-  bool error;
-  cost_t cost;
-  pcover fold;
-  int out_type = F_type;
+// Status codes reported to Rust, keep in sync with src/error.rs
+#define STATUS_OK 0
+#define STATUS_EMPTY_COVER 1
+#define STATUS_NO_PLA 2
+#define STATUS_VERIFY_FAILED 3
+#define STATUS_IO 4
+#define STATUS_WORKER_FAILED 5
+#define STATUS_FATAL 6
+#define STATUS_NOT_EQUIVALENT 7
+#define STATUS_SIZE_MISMATCH 8
+#define STATUS_BAD_RANGE 9
 
-  if (read_pla(fpla, TRUE, TRUE, FD_type, &PLA) == EOF) {
-    return NULL;
+// The espresso sources are built with exit and fatal renamed to the hooks below, see build.rs
+#undef exit
+#undef fatal
+extern void exit(int status);
+
+// Defined in reduce_toggle.c, see there
+extern void espresso_rs_reset_reduce(void);
+
+// Set while a run is active, fatal errors jump back to it instead of exiting
+static jmp_buf *fatal_jump = NULL;
+static char fatal_message[256];
+
+// Replaces exit() in the espresso sources
+void espresso_rs_exit(int status) {
+  if (fatal_jump == NULL) {
+    exit(status);
+  }
+  if (fatal_message[0] == '\0') {
+    snprintf(fatal_message, sizeof(fatal_message), "espresso exited with status %d", status);
+  }
+  longjmp(*fatal_jump, 1);
+}
+
+// Replaces fatal() of cvrmisc.c, see src/fatal.c
+void espresso_rs_fatal(char *s) {
+  snprintf(fatal_message, sizeof(fatal_message), "%s", s);
+  espresso_rs_exit(1);
+}
+
+// Minimization algorithms, keep in sync with Algorithm in src/options.rs
+#define ALGORITHM_ESPRESSO 0
+#define ALGORITHM_EXACT 1
+#define ALGORITHM_EXACT_LITERALS 2
+#define ALGORITHM_SIGNATURE 3
+#define ALGORITHM_SIMPLIFY 4
+#define ALGORITHM_SINGLE_OUTPUT 5
+
+// Mirrors the #[repr(C)] struct in src/options.rs
+typedef struct {
+  int single_expand;          /* -efast */
+  int use_super_gasp;         /* -estrong */
+  int remove_essential;       /* cleared by -eness */
+  int force_irredundant;      /* cleared by -enirr */
+  int unwrap_onset;           /* cleared by -enunwrap */
+  int recompute_onset;        /* -eonset */
+  int pos;                    /* -epos */
+  int use_random_order;       /* -erandom */
+  int algorithm;              /* one of the ALGORITHM_* defines */
+  int lex_sort;               /* -Dlexsort on the result */
+  int out_type;               /* -o, the sets printed by run_espresso */
+} espresso_options;
+
+// Stores the current values of the option globals in saved
+static void save_options(espresso_options *saved) {
+  saved->single_expand = single_expand;
+  saved->use_super_gasp = use_super_gasp;
+  saved->remove_essential = remove_essential;
+  saved->force_irredundant = force_irredundant;
+  saved->unwrap_onset = unwrap_onset;
+  saved->recompute_onset = recompute_onset;
+  saved->pos = pos;
+  saved->use_random_order = use_random_order;
+}
+
+// Sets the option globals to opts
+static void set_options(const espresso_options *opts) {
+  single_expand = opts->single_expand;
+  use_super_gasp = opts->use_super_gasp;
+  remove_essential = opts->remove_essential;
+  force_irredundant = opts->force_irredundant;
+  unwrap_onset = opts->unwrap_onset;
+  recompute_onset = opts->recompute_onset;
+  pos = opts->pos;
+  use_random_order = opts->use_random_order;
+}
+
+// Frees the global cube structure set up by read_pla
+static void cleanup_cube(void) {
+  FREE(cube.part_size);
+  if (cube.fullset != NULL) {
+    setdown_cube();           /* free the cube/cdata structure data */
+  }
+  sf_cleanup();               /* free unused set structures */
+  sm_cleanup();               /* sparse matrix cleanup */
+}
+
+// Frees the PLA and the global cube structure set up by read_pla
+static void cleanup_pla(pPLA PLA) {
+  free_PLA(PLA);
+  cleanup_cube();
+}
+
+// Reads the PLA from fpla, on failure the global state is reset for the next call
+static int read_input(FILE * fpla, pPLA *PLA) {
+  int status;
+
+  if (read_pla(fpla, TRUE, TRUE, FD_type, PLA) == EOF) {
+    // a PLA size without any cubes is an empty cover, anything else is not a PLA
+    status = cube.fullset != NULL ? STATUS_EMPTY_COVER : STATUS_NO_PLA;
+    cleanup_pla(*PLA);
+    return status;
   }
 
   // makes sure free() won't crash on this variable
-  PLA->filename = NULL;
+  (*PLA)->filename = NULL;
+  return STATUS_OK;
+}
+
+// Minimizes the ON-set of PLA with the selected algorithm, disposes of PLA->F
+static pcover minimize(pPLA PLA, int algorithm) {
+  pcover F;
+
+  switch (algorithm) {
+    case ALGORITHM_EXACT:
+      F = minimize_exact(PLA->F, PLA->D, PLA->R, TRUE);
+      free_cover(PLA->F);
+      return F;
+    case ALGORITHM_EXACT_LITERALS:
+      F = minimize_exact_literals(PLA->F, PLA->D, PLA->R, TRUE);
+      free_cover(PLA->F);
+      return F;
+    case ALGORITHM_SIGNATURE:
+      F = signature(PLA->F, PLA->D, PLA->R);
+      free_cover(PLA->F);
+      return F;
+    case ALGORITHM_SIMPLIFY:
+      // simplify only disposes of the cube list, not of the cover it points into
+      F = simplify(cube1list(PLA->F));
+      free_cover(PLA->F);
+      return F;
+    case ALGORITHM_SINGLE_OUTPUT:
+      // so_espresso replaces PLA->F and leaves skip_make_sparse set for the next run
+      so_espresso(PLA, 0);
+      skip_make_sparse = FALSE;
+      return PLA->F;
+    default:
+      return espresso(PLA->F, PLA->D, PLA->R);
+  }
+}
+
+// Writes the ON-set of PLA, fprint_pla omits .type f but readers default to fd without it
+static void print_onset(FILE *outfile, pPLA PLA) {
+  fprintf(outfile, ".type f\n");
+  fprint_pla(outfile, PLA, F_type);
+}
+
+// Minimizes PLA->F with the selected algorithm, if verification fails PLA->F is left unchanged
+static int minimize_pla(pPLA PLA, const espresso_options *opts) {
+  bool error;
+  cost_t cost;
+  pcover fold;
 
   fold = sf_save(PLA->F);
-  PLA->F = espresso(PLA->F, PLA->D, PLA->R);
+  PLA->F = minimize(PLA, opts->algorithm);
   EXECUTE(error = verify(PLA->F, fold, PLA->D), VERIFY_TIME, PLA->F, cost);
 
   if (error) {
+    free_cover(PLA->F);
     PLA->F = fold;
-    (void) check_consistency(PLA);
   } else {
     free_cover(fold);
   }
 
-  fprint_pla(outfile, PLA, out_type);
+  if (opts->lex_sort) {
+    PLA->F = lex_sort(PLA->F);
+  }
 
-  /* cleanup all used memory */
-  free_PLA(PLA);
-  FREE(cube.part_size);
-  setdown_cube();             /* free the cube/cdata structure data */
-  sf_cleanup();               /* free unused set structures */
-  sm_cleanup();               /* sparse matrix cleanup */
+  return error ? STATUS_VERIFY_FAILED : STATUS_OK;
 }
 
-char * run_d1merge(FILE * fpla, FILE *outfile) {
+// Runs the selected minimization with the given options and prints the sets of opts->out_type,
+// the option globals are restored afterwards
+int run_espresso(FILE * fpla, FILE *outfile, const espresso_options *opts) {
   pPLA PLA;
-  char * result = NULL;
-  int out_type = F_type;
+  int status;
+  espresso_options saved;
 
-  int first = -1;
-  int last = -1;
-  int i;
+  // -epos is applied while reading, so the options have to be set first
+  save_options(&saved);
+  set_options(opts);
 
-  if (read_pla(fpla, TRUE, TRUE, FD_type, &PLA) == EOF) {
-    return NULL;
+  if ((status = read_input(fpla, &PLA)) != STATUS_OK) {
+    set_options(&saved);
+    return status;
   }
 
-  // makes sure free() won't crash on this variable
-  PLA->filename = NULL;
+  status = minimize_pla(PLA, opts);
+  if (opts->out_type == F_type) {
+    print_onset(outfile, PLA);
+  } else {
+    fprint_pla(outfile, PLA, opts->out_type);
+  }
+
+  /* cleanup all used memory */
+  cleanup_pla(PLA);
+  set_options(&saved);
+
+  return status;
+}
+
+// Runs distance-1 merge on the variables first..=last, which have to be variables of the cube
+int run_d1merge(FILE * fpla, FILE *outfile, int first, int last) {
+  pPLA PLA;
+  int i;
+  int status;
 
-  if (first < 0 || first >= cube.num_vars) {
-      first = 0;
+  if ((status = read_input(fpla, &PLA)) != STATUS_OK) {
+    return status;
   }
-  if (last < 0 || last >= cube.num_vars) {
-      last = cube.num_vars - 1;
+
+  // first..last is inclusive, last == first - 1 is an empty range
+  if (first < 0 || last < first - 1 || last >= cube.num_vars) {
+    cleanup_pla(PLA);
+    return STATUS_BAD_RANGE;
   }
   for(i = first; i <= last; i++) {
       PLA->F = d1merge(PLA->F, i);
   }
 
-  fprint_pla(outfile, PLA, out_type);
+  print_onset(outfile, PLA);
 
   /* cleanup all used memory */
-  free_PLA(PLA);
-  FREE(cube.part_size);
-  setdown_cube();             /* free the cube/cdata structure data */
-  sf_cleanup();               /* free unused set structures */
-  sm_cleanup();               /* sparse matrix cleanup */
+  cleanup_pla(PLA);
+
+  return STATUS_OK;
 }
 
 FILE *create_file_with_contents(const char *data, unsigned int length) {
   FILE *f = tmpfile();
+  if (f == NULL) {
+    return NULL;
+  }
   fwrite(data, length, 1, f);
   rewind(f);
   return f;
@@ -106,35 +278,723 @@ char *read_file_contents(FILE *f) {
         return NULL;
     }
 
-    if (fread(buffer, length, 1, f) != 1) {
+    if (length > 0 && fread(buffer, length, 1, f) != 1) {
+        free(buffer);
         return NULL;
     }
     buffer[length] = '\0';
     return buffer;
 }
 
-char * run_espresso_from_data(const char *data, unsigned int length, char ** out) {
-  if (length == 0) {
-    return NULL;
+// Calls run(args), a fatal error inside espresso aborts the run with STATUS_FATAL and the message
+// in *message. Whatever the run had allocated at that point is leaked.
+static int run_guarded(int (*run)(void *), void *args, char **message) {
+  jmp_buf jump;
+  espresso_options saved;
+  int status;
+
+  save_options(&saved);
+  if (setjmp(jump) != 0) {
+    fatal_jump = NULL;
+    set_options(&saved);
+    skip_make_sparse = FALSE;
+    cleanup_cube();
+    *message = strdup(fatal_message);
+    return *message != NULL ? STATUS_FATAL : STATUS_IO;
   }
+  fatal_message[0] = '\0';
+  fatal_jump = &jump;
+  espresso_rs_reset_reduce();
 
-  FILE *tempPLA = create_file_with_contents(data, length);
-  FILE *outfile = tmpfile();
-  run_espresso(tempPLA, outfile);
-  fclose(tempPLA);
-  *out = read_file_contents(outfile);
-  fclose(outfile);
+  status = run(args);
+  fatal_jump = NULL;
+  return status;
 }
 
-char * run_d1merge_from_data(const char * data, unsigned int length, char ** out) {
+typedef struct {
+  int (*run)(FILE *, FILE *, void *);
+  void *args;
+  FILE *in;
+  FILE *out;
+} file_run;
+
+static int run_on_files(void *args) {
+  file_run *f = (file_run *) args;
+  return f->run(f->in, f->out, f->args);
+}
+
+// Runs one of the entry points above on data, *out is only set if the run produced output
+// or failed with STATUS_FATAL
+static int run_from_data(int (*run)(FILE *, FILE *, void *), void *args,
+                  const char *data, unsigned int length, char ** out) {
+  file_run files;
+  int status;
+
+  *out = NULL;
   if (length == 0) {
+    return STATUS_NO_PLA;
+  }
+
+  if ((files.in = create_file_with_contents(data, length)) == NULL) {
+    return STATUS_IO;
+  }
+  if ((files.out = tmpfile()) == NULL) {
+    fclose(files.in);
+    return STATUS_IO;
+  }
+  files.run = run;
+  files.args = args;
+
+  status = run_guarded(run_on_files, &files, out);
+  fclose(files.in);
+
+  if (status == STATUS_OK || status == STATUS_VERIFY_FAILED) {
+    *out = read_file_contents(files.out);
+    if (*out == NULL) {
+      status = STATUS_IO;
+    }
+  }
+  fclose(files.out);
+  return status;
+}
+
+static int run_espresso_args(FILE *fpla, FILE *outfile, void *args) {
+  return run_espresso(fpla, outfile, (const espresso_options *) args);
+}
+
+int run_espresso_from_data(const char *data, unsigned int length,
+                           const espresso_options *opts, char ** out) {
+  return run_from_data(run_espresso_args, (void *) opts, data, length, out);
+}
+
+// A PLA in espresso's cube representation, mirrors RawCovers in src/covers.rs
+typedef struct {
+  int num_binary_vars;
+  int num_vars;
+  const int *part_size;             /* sizes of all variables, 2 for the binary ones */
+  int pla_type;                     /* F_type, FD_type, FR_type or FDR_type */
+  int rows;                         /* number of rows the covers were built from */
+  const unsigned char *cubes[3];    /* F, D and R, one byte per part and cube */
+  int count[3];
+} espresso_covers;
+
+// Builds a cover from one byte per part and cube, nonzero bytes are the parts in the cube
+static pcover build_cover(const unsigned char *parts, int count) {
+  pcover T = new_cover(count);
+  pcube p;
+  int i, j;
+
+  for (i = 0; i < count; i++, parts += cube.size) {
+    p = GETSET(T, T->count++);
+    set_clear(p, cube.size);
+    for (j = 0; j < cube.size; j++) {
+      if (parts[j]) {
+        set_insert(p, j);
+      }
+    }
+  }
+  return T;
+}
+
+// Copies the cubes of T into a new array of one byte per part and cube
+static unsigned char *cover_to_parts(pcover T) {
+  unsigned char *parts;
+  unsigned char *q;
+  pcube last, p;
+  int j;
+
+  if ((parts = (unsigned char *) malloc((size_t) T->count * cube.size + 1)) == NULL) {
     return NULL;
   }
+  q = parts;
+  foreach_set(T, last, p) {
+    for (j = 0; j < cube.size; j++) {
+      *q++ = is_in_set(p, j) != 0;
+    }
+  }
+  return parts;
+}
+
+// Builds the PLA from covers the way read_pla does from text, the options have to be set already
+static pPLA build_pla(const espresso_covers *covers) {
+  pPLA PLA;
+  pcover X;
+  int var;
+
+  cube.num_binary_vars = covers->num_binary_vars;
+  cube.num_vars = covers->num_vars;
+  cube.part_size = ALLOC(int, cube.num_vars);
+  for (var = 0; var < cube.num_vars; var++) {
+    cube.part_size[var] = covers->part_size[var];
+  }
+  cube_setup();
+
+  PLA = new_PLA();
+  PLA->pla_type = covers->pla_type;
+  PLA->F = build_cover(covers->cubes[0], covers->count[0]);
+  PLA->D = build_cover(covers->cubes[1], covers->count[1]);
+  PLA->R = build_cover(covers->cubes[2], covers->count[2]);
+
+  /* Decide how to break PLA into ON-set, OFF-set and DC-set */
+  if (PLA->pla_type == F_type || PLA->pla_type == FD_type) {
+    free_cover(PLA->R);
+    PLA->R = complement(cube2list(PLA->F, PLA->D));
+  } else if (PLA->pla_type == FR_type) {
+    free_cover(PLA->D);
+    X = d1merge(sf_join(PLA->F, PLA->R), cube.num_vars - 1);
+    PLA->D = complement(cube1list(X));
+    free_cover(X);
+  }
+
+  if (pos) {
+    X = PLA->F;
+    PLA->F = PLA->R;
+    PLA->R = X;
+    PLA->phase = new_cube();
+    set_diff(PLA->phase, cube.fullset, cube.var_mask[cube.num_vars-1]);
+  }
+  return PLA;
+}
+
+typedef struct {
+  const espresso_covers *covers;
+  const espresso_options *opts;
+  unsigned char **cubes;
+  int *count;
+} covers_run;
+
+static int run_espresso_covers(void *args) {
+  covers_run *run = (covers_run *) args;
+  pPLA PLA;
+  int status;
+  espresso_options saved;
+
+  // like read_pla, a PLA without a single row is an empty cover
+  if (run->covers->rows == 0) {
+    return STATUS_EMPTY_COVER;
+  }
+
+  save_options(&saved);
+  set_options(run->opts);
+
+  PLA = build_pla(run->covers);
+  status = minimize_pla(PLA, run->opts);
+  *run->count = PLA->F->count;
+  if ((*run->cubes = cover_to_parts(PLA->F)) == NULL) {
+    status = STATUS_IO;
+  }
+
+  cleanup_pla(PLA);
+  set_options(&saved);
+
+  return status;
+}
+
+// Like run_espresso_from_data, but without going through PLA text: the resulting ON-set is
+// returned in *cubes (same layout as the input covers) and *count.
+// *message is only set if the run failed with STATUS_FATAL.
+int run_espresso_from_covers(const espresso_covers *covers, const espresso_options *opts,
+                             unsigned char **cubes, int *count, char **message) {
+  covers_run run;
+
+  *cubes = NULL;
+  *count = 0;
+  *message = NULL;
+  run.covers = covers;
+  run.opts = opts;
+  run.cubes = cubes;
+  run.count = count;
+  return run_guarded(run_espresso_covers, &run, message);
+}
+
+static int run_d1merge_args(FILE *fpla, FILE *outfile, void *args) {
+  int *range = (int *) args;
+  return run_d1merge(fpla, outfile, range[0], range[1]);
+}
+
+int run_d1merge_from_data(const char * data, unsigned int length, int first, int last, char ** out) {
+  int range[2];
+
+  range[0] = first;
+  range[1] = last;
+  return run_from_data(run_d1merge_args, range, data, length, out);
+}
+
+// Reads the PLA from fpla for the runs on whole covers, a PLA size without any cubes is an
+// empty cover here instead of an error. On failure nothing is left allocated but the cube.
+static int read_cover(FILE *fpla, int needs_offset, pPLA *PLA) {
+  if (read_pla(fpla, TRUE, needs_offset, FD_type, PLA) == EOF) {
+    if (cube.fullset == NULL) {
+      free_PLA(*PLA);
+      return STATUS_NO_PLA;
+    }
+    (*PLA)->F = new_cover(0);
+    (*PLA)->D = new_cover(0);
+    (*PLA)->R = new_cover(0);
+    // read_pla stops before computing the OFF-set of an empty cover, it is everything
+    if (needs_offset && ((*PLA)->pla_type == F_type || (*PLA)->pla_type == FD_type)) {
+      free_cover((*PLA)->R);
+      (*PLA)->R = complement(cube2list((*PLA)->F, (*PLA)->D));
+    }
+  }
+
+  (*PLA)->filename = NULL;
+  return STATUS_OK;
+}
+
+// Frees a PLA read with a cube of the given size, free_PLA assumes the current one for the labels
+static void free_pla_of_size(pPLA PLA, int size) {
+  int i;
+
+  if (PLA->label != NULL) {
+    for (i = 0; i < size; i++) {
+      FREE(PLA->label[i]);
+    }
+    FREE(PLA->label);
+  }
+  free_PLA(PLA);
+}
+
+// Reads two PLAs over the same variables. read_pla ignores the size of a second PLA once the
+// cube is set up, so the cube is set up again from the second one and compared to the first.
+static int read_pair(FILE *first, FILE *second, int needs_offset, pPLA *PLA1, pPLA *PLA2) {
+  int num_binary_vars, num_vars, size, *part_size;
+  int status, same_size;
+
+  if ((status = read_cover(first, needs_offset, PLA1)) != STATUS_OK) {
+    cleanup_cube();
+    return status;
+  }
+
+  num_binary_vars = cube.num_binary_vars;
+  num_vars = cube.num_vars;
+  size = cube.size;
+  part_size = cube.part_size;
+  cube.part_size = NULL;
+  cleanup_cube();
+
+  if ((status = read_cover(second, needs_offset, PLA2)) != STATUS_OK) {
+    FREE(part_size);
+    free_pla_of_size(*PLA1, size);
+    cleanup_cube();
+    return status;
+  }
+
+  same_size = cube.num_binary_vars == num_binary_vars && cube.num_vars == num_vars
+    && memcmp(cube.part_size, part_size, num_vars * sizeof(int)) == 0;
+  FREE(part_size);
+  if (!same_size) {
+    free_pla_of_size(*PLA1, size);
+    cleanup_pla(*PLA2);
+    return STATUS_SIZE_MISMATCH;
+  }
+  return STATUS_OK;
+}
+
+// Whether every column of the PLA is named, PLA_permute compares all labels
+static bool has_labels(pPLA PLA) {
+  int i;
+
+  if (PLA->label == NULL) {
+    return FALSE;
+  }
+  for (i = 0; i < cube.size; i++) {
+    if (PLA->label[i] == NULL) {
+      return FALSE;
+    }
+  }
+  return TRUE;
+}
+
+// The first cube of F that is not covered by G u D, or NULL if F is covered
+static pcube find_uncovered(pcover F, pcover G, pcover D) {
+  pcube p, last, *GD;
+  pcube uncovered = NULL;
+
+  GD = cube2list(G, D);
+  foreach_set(F, last, p) {
+    // a cube without any value of some variable is empty, cube_is_covered never covers it
+    if (cdist0(p, p) && ! cube_is_covered(GD, p)) {
+      uncovered = p;
+      break;
+    }
+  }
+  free_cubelist(GD);
+  return uncovered;
+}
+
+// Looks for a minterm of F outside of G u D like verify() does, writes flag followed by the
+// value of every variable in the minterm to outfile. Returns whether there was one.
+static bool write_uncovered(FILE *outfile, pcover F, pcover G, pcover D, int flag) {
+  pcube p, q;
+  pcover X, T;
+  int var, i;
+
+  if ((p = find_uncovered(F, G, D)) == NULL) {
+    return FALSE;
+  }
+
+  // every minterm of p # (G u D) is uncovered, take the first value of every variable
+  X = sf_join(G, D);
+  T = cb_sharp(p, X);
+  q = GETSET(T, 0);
+  fprintf(outfile, "%d", flag);
+  for (var = 0; var < cube.num_vars; var++) {
+    for (i = cube.first_part[var]; i <= cube.last_part[var]; i++) {
+      if (is_in_set(q, i)) {
+        fprintf(outfile, " %d", i - cube.first_part[var]);
+        break;
+      }
+    }
+  }
+  fprintf(outfile, "\n");
+  free_cover(T);
+  free_cover(X);
+  return TRUE;
+}
+
+// Checks the PLAs for equivalence like PLA_verify(minimized, original), but writes a minterm
+// the ON-sets differ on instead of failing on unnamed columns. Columns of minimized are only
+// matched to those of original if both name all of them.
+static int run_verify(FILE *foriginal, FILE *fminimized, FILE *outfile, void *args) {
+  pPLA original, minimized;
+  int status;
+
+  (void) args;
+  if ((status = read_pair(foriginal, fminimized, FALSE, &original, &minimized)) != STATUS_OK) {
+    return status;
+  }
+
+  status = STATUS_OK;
+  if (has_labels(original) && has_labels(minimized)) {
+    PLA_permute(minimized, original);
+    if (minimized->F->sf_size != original->F->sf_size) {
+      status = STATUS_SIZE_MISMATCH;
+    }
+  }
+
+  // only the DC-set of the original counts, as in verify(F, Fold, Dold)
+  if (status == STATUS_OK
+      && (write_uncovered(outfile, original->F, minimized->F, original->D, 1)
+          || write_uncovered(outfile, minimized->F, original->F, original->D, 0))) {
+    status = STATUS_NOT_EQUIVALENT;
+  }
+
+  free_PLA(original);
+  cleanup_pla(minimized);
+  return status;
+}
+
+typedef struct {
+  int (*run)(FILE *, FILE *, FILE *, void *);
+  void *args;
+  FILE *first;
+  FILE *second;
+  FILE *out;
+} pair_run;
+
+static int run_on_pair(void *args) {
+  pair_run *f = (pair_run *) args;
+  return f->run(f->first, f->second, f->out, f->args);
+}
+
+// Like run_from_data for the runs on two PLAs
+static int run_from_pair(int (*run)(FILE *, FILE *, FILE *, void *), void *args,
+                         const char *first, unsigned int first_length,
+                         const char *second, unsigned int second_length, char **out) {
+  pair_run files;
+  int status;
+
+  *out = NULL;
+  if (first_length == 0 || second_length == 0) {
+    return STATUS_NO_PLA;
+  }
+
+  if ((files.first = create_file_with_contents(first, first_length)) == NULL) {
+    return STATUS_IO;
+  }
+  if ((files.second = create_file_with_contents(second, second_length)) == NULL) {
+    fclose(files.first);
+    return STATUS_IO;
+  }
+  if ((files.out = tmpfile()) == NULL) {
+    fclose(files.first);
+    fclose(files.second);
+    return STATUS_IO;
+  }
+  files.run = run;
+  files.args = args;
+
+  status = run_guarded(run_on_pair, &files, out);
+  fclose(files.first);
+  fclose(files.second);
+
+  if (status == STATUS_OK || status == STATUS_NOT_EQUIVALENT) {
+    *out = read_file_contents(files.out);
+    if (*out == NULL) {
+      status = STATUS_IO;
+    }
+  }
+  fclose(files.out);
+  return status;
+}
+
+// Checks minimized for equivalence with original, on STATUS_NOT_EQUIVALENT *out holds 1 if the
+// minterm is in the ON-set of original (0 if in that of minimized) and the value of every
+// variable in it
+int run_verify_from_data(const char *original, unsigned int original_length,
+                         const char *minimized, unsigned int minimized_length, char **out) {
+  return run_from_pair(run_verify, NULL, original, original_length,
+                       minimized, minimized_length, out);
+}
+
+// Operations on two covers, keep in sync with CoverOp in src/lib.rs
+#define OP_UNION 0
+#define OP_INTERSECT 1
+#define OP_SHARP 2
+#define OP_DSHARP 3
+#define OP_XOR 4
+
+// Applies the operation to the ON-sets of the two PLAs like the commands of the same name in
+// main.c, writes the resulting ON-set with the labels of the first PLA
+static int run_cover_op(FILE *ffirst, FILE *fsecond, FILE *outfile, void *args) {
+  int op = *(int *) args;
+  pPLA PLA1, PLA2;
+  pcover F, T1, T2;
+  int status;
+
+  // only xor needs the OFF-sets
+  status = read_pair(ffirst, fsecond, op == OP_XOR, &PLA1, &PLA2);
+  if (status != STATUS_OK) {
+    return status;
+  }
+
+  switch (op) {
+    case OP_UNION:
+      // sf_union expects both families sorted by decreasing size, and disposes of them
+      F = sf_union(sf_contain(PLA1->F), sf_contain(PLA2->F));
+      PLA2->F = NULL;
+      break;
+    case OP_INTERSECT:
+      F = cv_intersect(PLA1->F, PLA2->F);
+      free_cover(PLA1->F);
+      break;
+    case OP_SHARP:
+      F = cv_sharp(PLA1->F, PLA2->F);
+      free_cover(PLA1->F);
+      break;
+    case OP_DSHARP:
+      F = cv_dsharp(PLA1->F, PLA2->F);
+      free_cover(PLA1->F);
+      break;
+    default:
+      T1 = cv_intersect(PLA1->F, PLA2->R);
+      T2 = cv_intersect(PLA2->F, PLA1->R);
+      F = sf_contain(sf_join(T1, T2));
+      free_cover(T1);
+      free_cover(T2);
+      free_cover(PLA1->F);
+      break;
+  }
+  PLA1->F = F;
+  print_onset(outfile, PLA1);
+
+  free_PLA(PLA2);
+  cleanup_pla(PLA1);
+  return STATUS_OK;
+}
+
+int run_cover_op_from_data(const char *first, unsigned int first_length,
+                           const char *second, unsigned int second_length, int op, char **out) {
+  return run_from_pair(run_cover_op, &op, first, first_length, second, second_length, out);
+}
+
+// Replaces the ON-set of the PLA with its OFF-set, which is minimized with opts unless opts is
+// NULL. If verification fails, the unminimized OFF-set is written.
+static int run_complement(FILE *fpla, FILE *outfile, void *args) {
+  const espresso_options *opts = (const espresso_options *) args;
+  espresso_options saved;
+  pPLA PLA;
+  pcover X;
+  int status;
+
+  save_options(&saved);
+  if (opts != NULL) {
+    set_options(opts);
+  }
+
+  if ((status = read_cover(fpla, TRUE, &PLA)) != STATUS_OK) {
+    cleanup_cube();
+    set_options(&saved);
+    return status;
+  }
+
+  X = PLA->F;
+  PLA->F = PLA->R;
+  PLA->R = X;
+  // espresso needs at least one cube, an empty OFF-set is minimal anyway
+  if (opts != NULL && PLA->F->count > 0) {
+    status = minimize_pla(PLA, opts);
+  }
+  print_onset(outfile, PLA);
+
+  cleanup_pla(PLA);
+  set_options(&saved);
+  return status;
+}
+
+int run_complement_from_data(const char *data, unsigned int length,
+                             const espresso_options *opts, char **out) {
+  return run_from_data(run_complement, (void *) opts, data, length, out);
+}
+
+// Writes 1 if the ON-set and DC-set of the PLA cover every minterm, 0 otherwise
+static int run_tautology(FILE *fpla, FILE *outfile, void *args) {
+  pPLA PLA;
+  int status;
+
+  (void) args;
+  if ((status = read_cover(fpla, FALSE, &PLA)) != STATUS_OK) {
+    cleanup_cube();
+    return status;
+  }
+
+  fprintf(outfile, "%d\n", tautology(cube2list(PLA->F, PLA->D)) ? 1 : 0);
+
+  cleanup_pla(PLA);
+  return STATUS_OK;
+}
+
+int run_tautology_from_data(const char *data, unsigned int length, char **out) {
+  return run_from_data(run_tautology, NULL, data, length, out);
+}
+
+// Writes 1 if the ON-set of the first PLA is covered by the ON-set and DC-set of the second,
+// 0 otherwise
+static int run_implies(FILE *ffirst, FILE *fsecond, FILE *outfile, void *args) {
+  pPLA PLA1, PLA2;
+  int status;
+
+  (void) args;
+  if ((status = read_pair(ffirst, fsecond, FALSE, &PLA1, &PLA2)) != STATUS_OK) {
+    return status;
+  }
+
+  fprintf(outfile, "%d\n", find_uncovered(PLA1->F, PLA2->F, PLA2->D) == NULL ? 1 : 0);
+
+  free_PLA(PLA1);
+  cleanup_pla(PLA2);
+  return STATUS_OK;
+}
+
+int run_implies_from_data(const char *first, unsigned int first_length,
+                          const char *second, unsigned int second_length, char **out) {
+  return run_from_pair(run_implies, NULL, first, first_length, second, second_length, out);
+}
+
+// Whether the covers describe the same function, check_equiv of equiv.c without its leaks
+static bool same_function(pcover A, pcover B) {
+  pcube *list;
+  pcube last, p;
+  bool same = TRUE;
+
+  list = cube1list(A);
+  foreach_set(B, last, p) {
+    if (! cube_is_covered(list, p)) {
+      same = FALSE;
+      break;
+    }
+  }
+  free_cubelist(list);
+
+  if (same) {
+    list = cube1list(B);
+    foreach_set(A, last, p) {
+      if (! cube_is_covered(list, p)) {
+        same = FALSE;
+        break;
+      }
+    }
+    free_cubelist(list);
+  }
+  return same;
+}
+
+// Writes "i j negated_i negated_j" for every pair of outputs i < j that are equivalent, possibly
+// after negating either of them, like find_equiv_outputs of equiv.c (espresso -Dequiv).
+// An output is compared as the complement of its OFF-set, a negated one as its OFF-set.
+static int run_equiv(FILE *fpla, FILE *outfile, void *args) {
+  pPLA PLA;
+  pcover *F, *R;
+  int i, j, n, status;
+
+  (void) args;
+  if ((status = read_cover(fpla, TRUE, &PLA)) != STATUS_OK) {
+    cleanup_cube();
+    return status;
+  }
+
+  n = cube.output >= 0 ? cube.part_size[cube.output] : 0;
+  F = ALLOC(pcover, n);
+  R = ALLOC(pcover, n);
+  for (i = 0; i < n; i++) {
+    R[i] = cof_output(PLA->R, cube.first_part[cube.output] + i);
+    F[i] = complement(cube1list(R[i]));
+  }
+
+  for (i = 0; i < n - 1; i++) {
+    for (j = i + 1; j < n; j++) {
+      if (same_function(F[i], F[j])) {
+        fprintf(outfile, "%d %d 0 0\n", i, j);
+      } else if (same_function(F[i], R[j])) {
+        fprintf(outfile, "%d %d 0 1\n", i, j);
+      } else if (same_function(R[i], F[j])) {
+        fprintf(outfile, "%d %d 1 0\n", i, j);
+      } else if (same_function(R[i], R[j])) {
+        fprintf(outfile, "%d %d 1 1\n", i, j);
+      }
+    }
+  }
+
+  for (i = 0; i < n; i++) {
+    free_cover(F[i]);
+    free_cover(R[i]);
+  }
+  FREE(F);
+  FREE(R);
+  cleanup_pla(PLA);
+  return STATUS_OK;
+}
+
+int run_equiv_from_data(const char *data, unsigned int length, char **out) {
+  return run_from_data(run_equiv, NULL, data, length, out);
+}
+
+// Replaces the ON-set of the PLA with all primes of its ON-set and DC-set, writes the first
+// max_returned of them unless max_returned is negative. All primes are generated regardless.
+static int run_primes(FILE *fpla, FILE *outfile, void *args) {
+  int max_returned = *(int *) args;
+  pPLA PLA;
+  pcover F;
+  int status;
+
+  if ((status = read_cover(fpla, FALSE, &PLA)) != STATUS_OK) {
+    cleanup_cube();
+    return status;
+  }
+
+  F = PLA->F;
+  PLA->F = primes_consensus(cube2list(F, PLA->D));
+  free_cover(F);
+  if (max_returned >= 0 && PLA->F->count > max_returned) {
+    PLA->F->count = max_returned;
+  }
+  print_onset(outfile, PLA);
+
+  cleanup_pla(PLA);
+  return STATUS_OK;
+}
 
-  FILE *tempPLA = create_file_with_contents(data, length);
-  FILE *outfile = tmpfile();
-  run_d1merge(tempPLA, outfile);
-  fclose(tempPLA);
-  *out = read_file_contents(outfile);
-  fclose(outfile);
+int run_primes_from_data(const char *data, unsigned int length, int max_returned, char **out) {
+  return run_from_data(run_primes, &max_returned, data, length, out);
 }
@@ -0,0 +1,167 @@
+//! Reading and writing CNFs in DIMACS format for `-Dcnf`.
+
+use std::fmt::Write;
+
+use espresso_rs::{espresso_cnf, try_espresso_cnf, EspressoError};
+use rustsat::{
+    instances::Cnf,
+    types::{Clause, Lit},
+};
+
+/// A CNF with the number of variables declared by its `p cnf` line.
+#[derive(Debug)]
+pub struct Dimacs {
+    pub num_vars: u32,
+    pub cnf: Cnf,
+}
+
+impl Dimacs {
+    /// Parses a DIMACS CNF, DIMACS variable `n` becomes variable index `n - 1`. Without a
+    /// `p cnf` line the number of variables is the largest one used.
+    pub fn parse(input: &str) -> Result<Dimacs, String> {
+        let mut declared = None;
+        let mut max_var = 0;
+        let mut cnf = Cnf::new();
+        let mut clause = Clause::new();
+
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with('c') || line.is_empty() {
+                continue;
+            }
+            // end marker of the SATLIB benchmarks
+            if line.starts_with('%') {
+                break;
+            }
+            if let Some(header) = line.strip_prefix('p') {
+                let fields: Vec<_> = header.split_whitespace().collect();
+                match fields.as_slice() {
+                    ["cnf", vars, _] => {
+                        declared = Some(vars.parse::<u32>().map_err(|_| {
+                            format!("line {}: bad number of variables \"{}\"", i + 1, vars)
+                        })?)
+                    }
+                    _ => return Err(format!("line {}: bad problem line \"{}\"", i + 1, line)),
+                }
+                continue;
+            }
+
+            for literal in line.split_whitespace() {
+                let literal: i64 = literal
+                    .parse()
+                    .map_err(|_| format!("line {}: bad literal \"{}\"", i + 1, literal))?;
+                if literal == 0 {
+                    cnf.add_clause(std::mem::replace(&mut clause, Clause::new()));
+                    continue;
+                }
+
+                let var = literal.unsigned_abs();
+                if var > u32::MAX as u64 || matches!(declared, Some(d) if var > d as u64) {
+                    return Err(format!("line {}: variable {} out of range", i + 1, var));
+                }
+                max_var = max_var.max(var as u32);
+                clause.add(Lit::new(var as u32 - 1, literal < 0));
+            }
+        }
+        // the last clause may omit its terminating 0
+        if !clause.is_empty() {
+            cnf.add_clause(clause);
+        }
+
+        Ok(Dimacs {
+            num_vars: declared.unwrap_or(max_var),
+            cnf,
+        })
+    }
+
+    /// The number of clauses and literals.
+    pub fn size(&self) -> (usize, usize) {
+        (self.cnf.len(), self.cnf.iter().map(Clause::len).sum())
+    }
+
+    /// Writes the CNF in DIMACS format, preceded by `comments`.
+    pub fn write(&self, comments: &[String]) -> String {
+        let mut out = String::new();
+        for comment in comments {
+            writeln!(out, "c {}", comment).unwrap();
+        }
+        writeln!(out, "p cnf {} {}", self.num_vars, self.cnf.len()).unwrap();
+        for clause in self.cnf.iter() {
+            for lit in clause.iter() {
+                let var = lit.vidx32() as i64 + 1;
+                write!(out, "{} ", if lit.is_neg() { -var } else { var }).unwrap();
+            }
+            writeln!(out, "0").unwrap();
+        }
+        out
+    }
+}
+
+/// Minimizes the CNF with [`espresso_cnf`] and writes the result with a header comment
+/// comparing the sizes. With `verify`, fails unless espresso verified the result to be
+/// equivalent to the input.
+pub fn minimize(input: &str, verify: bool) -> Result<String, String> {
+    let dimacs = Dimacs::parse(input)?;
+    let before = dimacs.size();
+
+    let cnf = if verify {
+        try_espresso_cnf(dimacs.cnf).map_err(|e| match e {
+            EspressoError::VerificationFailed => {
+                "the minimized CNF could not be verified to be equivalent".to_owned()
+            }
+            e => e.to_string(),
+        })?
+    } else {
+        espresso_cnf(dimacs.cnf)
+    };
+    let result = Dimacs {
+        num_vars: dimacs.num_vars,
+        cnf,
+    };
+    let after = result.size();
+
+    let mut comments = vec![
+        "minimized by espresso-rs".to_owned(),
+        format!("before: {} clauses, {} literals", before.0, before.1),
+        format!("after: {} clauses, {} literals", after.0, after.1),
+    ];
+    if verify {
+        comments.push("verified: equivalent to the input".to_owned());
+    }
+
+    Ok(result.write(&comments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{minimize, Dimacs};
+
+    #[test]
+    fn test_parse_and_write() {
+        let input = "c example\np cnf 3 2\n1 -2 0\n2\n3 0\n";
+
+        let dimacs = Dimacs::parse(input).unwrap();
+
+        assert_eq!(dimacs.num_vars, 3);
+        assert_eq!(dimacs.size(), (2, 4));
+        assert_eq!(dimacs.write(&[]), "p cnf 3 2\n1 -2 0\n2 3 0\n");
+        assert!(Dimacs::parse("p cnf 2 1\n1 3 0\n").is_err());
+        assert!(Dimacs::parse("p cnf 2 1\n1 x 0\n").is_err());
+    }
+
+    #[test]
+    fn test_minimize() {
+        // (x1 | x2) & (x1 | !x2) is x1
+        let output = minimize("p cnf 2 2\n1 2 0\n1 -2 0\n", true).unwrap();
+
+        assert_eq!(
+            output,
+            "c minimized by espresso-rs\n\
+             c before: 2 clauses, 4 literals\n\
+             c after: 1 clauses, 1 literals\n\
+             c verified: equivalent to the input\n\
+             p cnf 2 1\n\
+             1 0\n"
+        );
+    }
+}
@@ -0,0 +1,508 @@
+//! A command line front-end mirroring the espresso executable.
+//!
+//! Every subcommand runs through the public API of the crate, so the results are the same as
+//! calling the library directly.
+
+mod dimacs;
+
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+use espresso_rs::{
+    serve_worker, try_d1merge, try_espresso_with, try_verify, Algorithm, EspressoError,
+    EspressoOptions, EspressoPool, PlaBinary, PlaMultiValued, PlaType, PLA,
+};
+
+const USAGE: &str = "\
+usage: espresso-rs [options] [file] [file2]
+
+  -D command     run command instead of ESPRESSO, one of
+                   ESPRESSO, exact, signature, so, simplify, echo, d1merge,
+                   cnf (minimize a CNF in DIMACS format with espresso_cnf),
+                   verify, PLAverify (check that file2 is equivalent to file, columns
+                   are matched by name if both PLAs name all of them),
+                   union, intersect, sharp, dsharp, xor (combine the binary PLAs in
+                   file and file2),
+                   taut (check whether the PLA is a tautology, don't-cares count as on),
+                   equiv (list the outputs that are equal, possibly negated),
+                   primes (all primes of the ON-set and DC-set of a binary PLA)
+  -e option      set an espresso option, one of
+                   fast, ness, nirr, nunwrap, onset, pos, random, strong
+  -o type        output type of the minimized PLA, one of f, fd, fr, fdr (the ON-set
+                 followed by the DC-set and/or OFF-set espresso used)
+  -r first-last  variables d1merge is applied to, all by default
+  -V             fail unless the minimized CNF is verified to be equivalent (cnf)
+  -x             do not print the result
+  -h             print this help
+  --worker       serve one run of an EspressoPool on standard input and output
+
+The PLA (or CNF) is read from file, or from standard input if no file (or -) is given.
+Only the commands comparing two PLAs take file2.";
+
+/// The subcommands of `-D`, see `option_table` in espresso's main.h.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// A command transforming a single PLA, see [`run`].
+    Pla(PlaCommand),
+    /// An operation on the covers of two binary PLAs, see [`run_combine`].
+    Combine(CoverCommand),
+    Cnf,
+    Verify,
+    Taut,
+    Equiv,
+    Primes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlaCommand {
+    Minimize(Algorithm),
+    Echo,
+    D1merge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CoverCommand {
+    Union,
+    Intersect,
+    Sharp,
+    Dsharp,
+    Xor,
+}
+
+impl Command {
+    fn parse(name: &str) -> Option<Command> {
+        Some(match name {
+            "ESPRESSO" => Command::Pla(PlaCommand::Minimize(Algorithm::Espresso)),
+            "exact" => Command::Pla(PlaCommand::Minimize(Algorithm::Exact)),
+            "signature" => Command::Pla(PlaCommand::Minimize(Algorithm::Signature)),
+            "so" | "single_output" => Command::Pla(PlaCommand::Minimize(Algorithm::SingleOutput)),
+            "simplify" => Command::Pla(PlaCommand::Minimize(Algorithm::Simplify)),
+            "echo" => Command::Pla(PlaCommand::Echo),
+            "d1merge" => Command::Pla(PlaCommand::D1merge),
+            "cnf" => Command::Cnf,
+            "verify" | "PLAverify" => Command::Verify,
+            "union" => Command::Combine(CoverCommand::Union),
+            "intersect" => Command::Combine(CoverCommand::Intersect),
+            "sharp" => Command::Combine(CoverCommand::Sharp),
+            "dsharp" => Command::Combine(CoverCommand::Dsharp),
+            "xor" => Command::Combine(CoverCommand::Xor),
+            "taut" => Command::Taut,
+            "equiv" => Command::Equiv,
+            "primes" => Command::Primes,
+            _ => return None,
+        })
+    }
+
+    /// The number of PLAs the command reads, see `num_plas` in espresso's main.h.
+    fn num_plas(self) -> usize {
+        match self {
+            Command::Verify | Command::Combine(_) => 2,
+            Command::Pla(_) | Command::Cnf | Command::Taut | Command::Equiv | Command::Primes => 1,
+        }
+    }
+}
+
+/// Applies an option of `-e`, see `esp_opt_table` in espresso's main.h.
+fn espresso_option(options: EspressoOptions, name: &str) -> Option<EspressoOptions> {
+    Some(match name {
+        "fast" => options.single_expand(true),
+        "ness" => options.remove_essential(false),
+        "nirr" => options.force_irredundant(false),
+        "nunwrap" => options.unwrap_onset(false),
+        "onset" => options.recompute_onset(true),
+        "pos" => options.pos(true),
+        "random" => options.use_random_order(true),
+        "strong" => options.use_super_gasp(true),
+        _ => return None,
+    })
+}
+
+#[derive(Debug)]
+struct Args {
+    command: Command,
+    options: EspressoOptions,
+    out_type: PlaType,
+    range: Option<(usize, usize)>,
+    verify: bool,
+    print: bool,
+    help: bool,
+    files: Vec<String>,
+}
+
+/// Parses the arguments like espresso's getopt string `D:e:o:r:Vx`, values may be attached to
+/// the flag (`-Dexact`) or follow it (`-D exact`).
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        command: Command::Pla(PlaCommand::Minimize(Algorithm::Espresso)),
+        options: EspressoOptions::default(),
+        out_type: PlaType::F,
+        range: None,
+        verify: false,
+        print: true,
+        help: false,
+        files: Vec::new(),
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-" || !arg.starts_with('-') {
+            parsed.files.push(arg);
+            continue;
+        }
+
+        let flag = arg[1..].chars().next().unwrap_or_default();
+        let attached = &arg[1 + flag.len_utf8()..];
+        let mut value = || {
+            if !attached.is_empty() {
+                Ok(attached.to_owned())
+            } else {
+                args.next()
+                    .ok_or_else(|| format!("option -{} requires an argument", flag))
+            }
+        };
+        match flag {
+            'D' => {
+                let name = value()?;
+                parsed.command =
+                    Command::parse(&name).ok_or_else(|| format!("bad subcommand \"{}\"", name))?;
+            }
+            'e' => {
+                let name = value()?;
+                parsed.options = espresso_option(parsed.options, &name)
+                    .ok_or_else(|| format!("bad espresso option \"{}\"", name))?;
+            }
+            'o' => {
+                let name = value()?;
+                parsed.out_type = match name.as_str() {
+                    "f" => PlaType::F,
+                    "fd" => PlaType::Fd,
+                    "fr" => PlaType::Fr,
+                    "fdr" => PlaType::Fdr,
+                    _ => {
+                        return Err(format!(
+                            "unsupported output type \"{}\", one of f, fd, fr, fdr",
+                            name
+                        ))
+                    }
+                };
+            }
+            'r' => {
+                let range = value()?;
+                parsed.range = range
+                    .split_once('-')
+                    .and_then(|(first, last)| Some((first.parse().ok()?, last.parse().ok()?)))
+                    .map(Some)
+                    .ok_or_else(|| format!("bad variable range \"{}\"", range))?;
+            }
+            'V' => parsed.verify = true,
+            'x' => parsed.print = false,
+            'h' => parsed.help = true,
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+
+    if parsed.out_type != PlaType::F
+        && !matches!(parsed.command, Command::Pla(PlaCommand::Minimize(_)))
+    {
+        return Err(format!(
+            "-o {} is only supported when minimizing",
+            parsed.out_type
+        ));
+    }
+    if parsed.files.len() > parsed.command.num_plas() {
+        return Err("trailing arguments on command line".to_owned());
+    }
+
+    Ok(parsed)
+}
+
+fn read_input(file: Option<&str>) -> Result<String, String> {
+    match file {
+        None | Some("-") => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| format!("cannot read standard input: {}", e))?;
+            Ok(input)
+        }
+        Some(file) => fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file, e)),
+    }
+}
+
+/// Whether the PLA text declares multi-valued variables (`.mv`).
+fn is_multi_valued(input: &str) -> bool {
+    input.lines().any(|l| l.trim_start().starts_with(".mv"))
+}
+
+/// Runs the command on a PLA of either kind, `num_vars` is the number of variables of the cube
+/// including the output.
+fn run<P: PLA>(
+    pla: P,
+    num_vars: usize,
+    command: PlaCommand,
+    args: &Args,
+) -> Result<P, EspressoError> {
+    match command {
+        PlaCommand::Minimize(algorithm) => {
+            let options = args.options.clone().algorithm(algorithm);
+            try_espresso_with(pla, &options.output_type(args.out_type))
+        }
+        PlaCommand::Echo => Ok(pla),
+        PlaCommand::D1merge => {
+            // a range outside the cube is reported by try_d1merge
+            let (first, last) = args.range.unwrap_or((0, num_vars - 1));
+            try_d1merge(pla, first..last.saturating_add(1))
+        }
+    }
+}
+
+/// Runs the command on the PLA in `input`, returns the text to print.
+fn run_pla(input: &str, command: PlaCommand, args: &Args) -> Result<String, String> {
+    let output = if is_multi_valued(input) {
+        let pla = PlaMultiValued::parse(input).map_err(|e| e.to_string())?;
+        let num_vars = pla.num_vars();
+        run(pla, num_vars, command, args)
+            .map_err(|e| e.to_string())?
+            .to_string()
+    } else {
+        let pla = PlaBinary::parse(input).map_err(|e| e.to_string())?;
+        let num_vars = pla.rows().next().map_or(1, |(inputs, _)| inputs.len() + 1);
+        run(pla, num_vars, command, args)
+            .map_err(|e| e.to_string())?
+            .to_string()
+    };
+
+    Ok(output)
+}
+
+/// Checks the PLA in `second` for equivalence with the one in `first`.
+fn run_verify(first: &str, second: &str) -> Result<String, String> {
+    let counterexample = if is_multi_valued(first) {
+        let original = PlaMultiValued::parse(first).map_err(|e| e.to_string())?;
+        let minimized = PlaMultiValued::parse(second).map_err(|e| e.to_string())?;
+        try_verify(&original, &minimized)
+    } else {
+        let original = PlaBinary::parse(first).map_err(|e| e.to_string())?;
+        let minimized = PlaBinary::parse(second).map_err(|e| e.to_string())?;
+        try_verify(&original, &minimized)
+    };
+
+    match counterexample.map_err(|e| e.to_string())? {
+        None => Ok("PLA's compared equal\n".to_owned()),
+        Some(counterexample) => Err(format!(
+            "PLA comparison failed; the PLA's are not equivalent: {}",
+            counterexample
+        )),
+    }
+}
+
+/// Checks whether the PLA in `input` is a tautology.
+fn run_taut(input: &str) -> Result<String, String> {
+    let taut = if is_multi_valued(input) {
+        PlaMultiValued::parse(input).and_then(|pla| pla.is_tautology())
+    } else {
+        PlaBinary::parse(input).and_then(|pla| pla.is_tautology())
+    };
+
+    Ok(format!(
+        "PLA is{}a tautology\n",
+        if taut.map_err(|e| e.to_string())? {
+            " "
+        } else {
+            " not "
+        }
+    ))
+}
+
+/// Lists the equivalent outputs of the PLA in `input` the way espresso does.
+fn run_equiv(input: &str) -> Result<String, String> {
+    let (equivalent, output_var, labels) = if is_multi_valued(input) {
+        let pla = PlaMultiValued::parse(input).map_err(|e| e.to_string())?;
+        let output_var = pla.num_vars() - 1;
+        let labels = pla.labels(output_var).map(<[String]>::to_vec);
+        (pla.equivalent_outputs(), output_var, labels)
+    } else {
+        let pla = PlaBinary::parse(input).map_err(|e| e.to_string())?;
+        let output_var = pla
+            .rows()
+            .next()
+            .map(|(inputs, _)| inputs.len())
+            .or_else(|| pla.input_labels().map(<[String]>::len))
+            .unwrap_or(0);
+        let labels = pla.output_labels().map(<[String]>::to_vec);
+        (pla.equivalent_outputs(), output_var, labels)
+    };
+    // unnamed outputs are named like espresso's makeup_labels does
+    let label = |i: usize| match &labels {
+        Some(labels) => labels[i].clone(),
+        None => format!("v{}.{}", output_var, i),
+    };
+    let not = |negated: bool| if negated { "NOT " } else { "" };
+
+    let mut output = String::new();
+    for pair in equivalent.map_err(|e| e.to_string())? {
+        output.push_str(&format!(
+            "# Outputs {}{} and {}{} ({} and {}) are equivalent\n",
+            not(pair.first_negated),
+            pair.first,
+            not(pair.second_negated),
+            pair.second,
+            label(pair.first),
+            label(pair.second)
+        ));
+    }
+    if output.is_empty() {
+        output.push_str("# No outputs are equivalent\n");
+    }
+    Ok(output)
+}
+
+/// Generates all primes of the binary PLA in `input`.
+fn run_primes(input: &str) -> Result<String, String> {
+    if is_multi_valued(input) {
+        return Err("only the primes of binary PLAs can be generated".to_owned());
+    }
+
+    PlaBinary::parse(input)
+        .and_then(|pla| pla.primes(None))
+        .map(|primes| primes.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Runs one of the operations on the covers of two binary PLAs.
+fn run_combine(first: &str, second: &str, command: CoverCommand) -> Result<String, String> {
+    if is_multi_valued(first) {
+        return Err("only binary PLAs can be combined".to_owned());
+    }
+    let first = PlaBinary::parse(first).map_err(|e| e.to_string())?;
+    let second = PlaBinary::parse(second).map_err(|e| e.to_string())?;
+
+    let result = match command {
+        CoverCommand::Union => first.union(&second),
+        CoverCommand::Intersect => first.intersect(&second),
+        CoverCommand::Sharp => first.sharp(&second),
+        CoverCommand::Dsharp => first.disjoint_sharp(&second),
+        CoverCommand::Xor => first.xor(&second),
+    };
+
+    result.map(|pla| pla.to_string()).map_err(|e| e.to_string())
+}
+
+fn main() {
+    if std::env::args().nth(1).as_deref() == Some(EspressoPool::WORKER_ARG) {
+        if let Err(e) = serve_worker() {
+            eprintln!("espresso-rs: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let args = parse_args(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("espresso-rs: {}\n\n{}", e, USAGE);
+        process::exit(1);
+    });
+    if args.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let input = |i: usize| read_input(args.files.get(i).map(String::as_str));
+    let result = match args.command {
+        Command::Cnf => input(0).and_then(|input| dimacs::minimize(&input, args.verify)),
+        Command::Verify => input(0).and_then(|first| run_verify(&first, &input(1)?)),
+        Command::Taut => input(0).and_then(|input| run_taut(&input)),
+        Command::Equiv => input(0).and_then(|input| run_equiv(&input)),
+        Command::Primes => input(0).and_then(|input| run_primes(&input)),
+        Command::Combine(command) => {
+            input(0).and_then(|first| run_combine(&first, &input(1)?, command))
+        }
+        Command::Pla(command) => input(0).and_then(|input| run_pla(&input, command, &args)),
+    };
+
+    match result {
+        Ok(output) => {
+            if args.print {
+                print!("{}", output);
+            }
+        }
+        Err(e) => {
+            eprintln!("espresso-rs: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use espresso_rs::{Algorithm, PlaType};
+
+    use super::{parse_args, Command, CoverCommand, PlaCommand};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = parse_args(args(&["-Dexact", "-e", "fast", "-o", "fd", "in.pla"])).unwrap();
+        assert_eq!(
+            parsed.command,
+            Command::Pla(PlaCommand::Minimize(Algorithm::Exact))
+        );
+        assert_eq!(parsed.out_type, PlaType::Fd);
+        assert_eq!(parsed.files, vec!["in.pla"]);
+        assert_eq!(parse_args(args(&["-ofdr"])).unwrap().out_type, PlaType::Fdr);
+
+        let parsed = parse_args(args(&["-D", "d1merge", "-r", "1-2", "-x"])).unwrap();
+        assert_eq!(parsed.command, Command::Pla(PlaCommand::D1merge));
+        assert_eq!(parsed.range, Some((1, 2)));
+        assert!(!parsed.print);
+        assert!(parsed.files.is_empty());
+
+        let parsed = parse_args(args(&["-Dcnf", "-V", "-"])).unwrap();
+        assert_eq!(parsed.command, Command::Cnf);
+        assert!(parsed.verify);
+        assert_eq!(parsed.files, vec!["-"]);
+
+        let parsed = parse_args(args(&["-DPLAverify", "a.pla", "b.pla"])).unwrap();
+        assert_eq!(parsed.command, Command::Verify);
+        assert_eq!(parsed.files, vec!["a.pla", "b.pla"]);
+
+        let parsed = parse_args(args(&["-D", "dsharp", "a.pla", "b.pla"])).unwrap();
+        assert_eq!(parsed.command, Command::Combine(CoverCommand::Dsharp));
+
+        let parsed = parse_args(args(&["-Dtaut"])).unwrap();
+        assert_eq!(parsed.command, Command::Taut);
+
+        let parsed = parse_args(args(&["-D", "equiv"])).unwrap();
+        assert_eq!(parsed.command, Command::Equiv);
+
+        let parsed = parse_args(args(&["-Dprimes", "in.pla"])).unwrap();
+        assert_eq!(parsed.command, Command::Primes);
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert_eq!(
+            parse_args(args(&["-Dfoo"])).unwrap_err(),
+            "bad subcommand \"foo\""
+        );
+        assert_eq!(
+            parse_args(args(&["-e", "slow"])).unwrap_err(),
+            "bad espresso option \"slow\""
+        );
+        assert!(parse_args(args(&["-o"])).is_err());
+        assert!(parse_args(args(&["-o", "kiss"])).is_err());
+        assert_eq!(
+            parse_args(args(&["-Decho", "-o", "fr"])).unwrap_err(),
+            "-o fr is only supported when minimizing"
+        );
+        assert_eq!(
+            parse_args(args(&["-Dd1merge", "-r", "5"])).unwrap_err(),
+            "bad variable range \"5\""
+        );
+        assert!(parse_args(args(&["a.pla", "b.pla"])).is_err());
+        assert!(parse_args(args(&["-Dverify", "a.pla", "b.pla", "c.pla"])).is_err());
+    }
+}
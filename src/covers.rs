@@ -0,0 +1,243 @@
+use std::os::raw::{c_char, c_int, c_uchar, c_void};
+use std::{ptr, slice};
+
+use crate::{
+    error::{STATUS_FATAL, STATUS_VERIFY_FAILED},
+    lock_espresso,
+    options::RawOptions,
+    take_c_string, EspressoError, EspressoOptions, OutputVal, PlaType,
+};
+
+extern "C" {
+    fn run_espresso_from_covers(
+        covers: *const RawCovers,
+        opts: *const RawOptions,
+        cubes: *mut *mut c_uchar,
+        count: *mut c_int,
+        message: *mut *mut c_char,
+    ) -> c_int;
+    fn free(p: *mut c_void);
+}
+
+const ON_SET: usize = 0;
+const DC_SET: usize = 1;
+const OFF_SET: usize = 2;
+
+/// A PLA in espresso's cube representation, exchanged with the C core without going through
+/// PLA text.
+///
+/// Every cube is stored as one byte per part: two parts per binary variable (value 0 and value 1)
+/// followed by one part per value of each multi-valued variable, the outputs are the last one.
+#[doc(hidden)]
+pub struct Covers {
+    num_binary_vars: usize,
+    part_sizes: Vec<usize>,
+    pla_type: PlaType,
+    rows: usize,
+    // ON-set, don't-care set and OFF-set
+    sets: [Vec<u8>; 3],
+}
+
+impl Covers {
+    /// Creates empty covers with `num_binary_vars` binary variables followed by multi-valued
+    /// variables of the given sizes.
+    pub(crate) fn new(num_binary_vars: usize, mv_sizes: &[usize], pla_type: PlaType) -> Self {
+        let mut part_sizes = vec![2; num_binary_vars];
+        part_sizes.extend_from_slice(mv_sizes);
+
+        Covers {
+            num_binary_vars,
+            part_sizes,
+            pla_type,
+            rows: 0,
+            sets: [Vec::new(), Vec::new(), Vec::new()],
+        }
+    }
+
+    pub(crate) fn num_binary_vars(&self) -> usize {
+        self.num_binary_vars
+    }
+
+    /// The number of values of the last variable, the outputs.
+    pub(crate) fn num_outputs(&self) -> usize {
+        self.part_sizes.last().copied().unwrap_or(0)
+    }
+
+    pub(crate) fn pla_type(&self) -> PlaType {
+        self.pla_type
+    }
+
+    /// The number of parts of a cube.
+    pub(crate) fn size(&self) -> usize {
+        self.part_sizes.iter().sum()
+    }
+
+    /// Adds a row the way espresso reads it from text: `inputs` holds the parts of all variables
+    /// but the last, `outputs` the values of the last one.
+    pub(crate) fn push_row(&mut self, inputs: &[u8], outputs: &[OutputVal]) {
+        self.rows += 1;
+
+        let sets = match self.pla_type {
+            PlaType::F => [true, false, false],
+            PlaType::Fd => [true, true, false],
+            PlaType::Fr => [true, false, true],
+            PlaType::Fdr => [true, true, true],
+        };
+        for (set, value) in [
+            (ON_SET, OutputVal::On),
+            (DC_SET, OutputVal::DontCare),
+            (OFF_SET, OutputVal::Off),
+        ] {
+            if sets[set] && outputs.contains(&value) {
+                self.sets[set].extend_from_slice(inputs);
+                self.sets[set].extend(outputs.iter().map(|&o| (o == value) as u8));
+            }
+        }
+    }
+
+    /// The cubes of the ON-set.
+    pub(crate) fn on_set(&self) -> impl Iterator<Item = &[u8]> {
+        self.sets[ON_SET].chunks(self.size().max(1))
+    }
+}
+
+/// The covers as passed to api.c, keep in sync with `espresso_covers` there.
+#[repr(C)]
+struct RawCovers {
+    num_binary_vars: c_int,
+    num_vars: c_int,
+    part_size: *const c_int,
+    pla_type: c_int,
+    rows: c_int,
+    cubes: [*const c_uchar; 3],
+    count: [c_int; 3],
+}
+
+/// Minimizes the covers, returning the resulting ON-set and whether it passed verification.
+/// If verification failed, the result is the unminimized ON-set.
+pub(crate) fn minimize(
+    covers: &Covers,
+    options: &EspressoOptions,
+) -> Result<(Covers, bool), EspressoError> {
+    let size = covers.size();
+    let part_size: Vec<c_int> = covers.part_sizes.iter().map(|&s| s as c_int).collect();
+    let raw_covers = RawCovers {
+        num_binary_vars: covers.num_binary_vars as c_int,
+        num_vars: covers.part_sizes.len() as c_int,
+        part_size: part_size.as_ptr(),
+        pla_type: covers.pla_type.to_raw(),
+        rows: covers.rows as c_int,
+        cubes: [0, 1, 2].map(|set| covers.sets[set].as_ptr()),
+        count: [0, 1, 2].map(|set| (covers.sets[set].len() / size.max(1)) as c_int),
+    };
+    let raw_options = options.to_raw();
+
+    let mut cubes: *mut c_uchar = ptr::null_mut();
+    let mut count: c_int = 0;
+    let mut message: *mut c_char = ptr::null_mut();
+    let status = {
+        let _guard = lock_espresso();
+        unsafe {
+            run_espresso_from_covers(
+                &raw_covers,
+                &raw_options,
+                &mut cubes,
+                &mut count,
+                &mut message,
+            )
+        }
+    };
+
+    let message = take_c_string(message);
+    let on_set = if cubes.is_null() {
+        Vec::new()
+    } else {
+        let on_set = unsafe { slice::from_raw_parts(cubes, count as usize * size).to_vec() };
+        unsafe { free(cubes as *mut c_void) };
+        on_set
+    };
+
+    let verified = status != STATUS_VERIFY_FAILED;
+    if verified {
+        match (status, message) {
+            (STATUS_FATAL, Some(message)) => return Err(EspressoError::Fatal(message)),
+            (status, _) => EspressoError::from_status(status)?,
+        }
+    }
+
+    let mut result = Covers::new(covers.num_binary_vars, &[], PlaType::F);
+    result.part_sizes = covers.part_sizes.clone();
+    result.rows = count as usize;
+    result.sets[ON_SET] = on_set;
+
+    Ok((result, verified))
+}
+
+#[cfg(test)]
+mod tests {
+    use rustsat::types::TernaryVal;
+
+    use crate::{minimize_string, EspressoOptions, OutputVal, PlaBinary, PlaType, PLA};
+
+    #[test]
+    fn test_same_as_text() {
+        let value = |c| match c {
+            '1' => TernaryVal::True,
+            '0' => TernaryVal::False,
+            _ => TernaryVal::DontCare,
+        };
+        let rows = [
+            ("0000", "1-"),
+            ("0001", "10"),
+            ("0101", "~1"),
+            ("0111", "01"),
+            ("1-10", "-1"),
+            ("1100", "0~"),
+        ];
+
+        for pla_type in [PlaType::F, PlaType::Fd, PlaType::Fr, PlaType::Fdr] {
+            for pos in [false, true] {
+                let mut pla = PlaBinary::with_type(pla_type);
+                for (inputs, outputs) in rows {
+                    pla.add_row(
+                        inputs.chars().map(value).collect(),
+                        outputs
+                            .chars()
+                            .map(|c| OutputVal::from_char(c).unwrap())
+                            .collect(),
+                    );
+                }
+                let options = EspressoOptions::default().pos(pos);
+
+                let (text, _) = minimize_string(&pla.to_string(), &options).unwrap();
+                let (direct, _) =
+                    super::minimize(&pla.to_covers().unwrap().unwrap(), &options).unwrap();
+                let text = PlaBinary::parse(&text).unwrap();
+                let direct = pla.with_covers(direct).unwrap();
+
+                assert_eq!(direct.to_string(), text.to_string());
+            }
+        }
+    }
+    #[test]
+    fn test_empty_result() {
+        let dont_cares = PlaBinary::parse(".i 2\n.o 1\n.type fd\n0- -\n11 -\n.e\n").unwrap();
+        let tautology = PlaBinary::parse(".i 2\n.o 1\n-- 1\n.e\n").unwrap();
+
+        for (pla, pos) in [(dont_cares, false), (tautology, true)] {
+            let options = EspressoOptions::default().pos(pos);
+
+            let (text, _) = minimize_string(&pla.to_string(), &options).unwrap();
+            let (direct, _) =
+                super::minimize(&pla.to_covers().unwrap().unwrap(), &options).unwrap();
+            let direct = pla.with_covers(direct).unwrap();
+
+            assert_eq!(direct.rows().count(), 0);
+            assert_eq!(
+                direct.to_string(),
+                PlaBinary::parse(&text).unwrap().to_string()
+            );
+            assert!(direct.to_string().starts_with(".i 2\n.o 1\n"));
+        }
+    }
+}
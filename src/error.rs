@@ -0,0 +1,99 @@
+use std::{error::Error, fmt::Display, os::raw::c_int};
+
+// Status codes returned by api.c, keep in sync with the defines there
+pub(crate) const STATUS_OK: c_int = 0;
+pub(crate) const STATUS_EMPTY_COVER: c_int = 1;
+pub(crate) const STATUS_NO_PLA: c_int = 2;
+pub(crate) const STATUS_VERIFY_FAILED: c_int = 3;
+pub(crate) const STATUS_IO: c_int = 4;
+pub(crate) const STATUS_WORKER_FAILED: c_int = 5;
+pub(crate) const STATUS_FATAL: c_int = 6;
+pub(crate) const STATUS_NOT_EQUIVALENT: c_int = 7;
+pub(crate) const STATUS_SIZE_MISMATCH: c_int = 8;
+pub(crate) const STATUS_BAD_RANGE: c_int = 9;
+
+/// Errors reported by the fallible (`try_*`) variants of the espresso functions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EspressoError {
+    /// A line of PLA text could not be parsed.
+    MalformedPla { line: usize, message: String },
+    /// The input describes a PLA without a single cube.
+    EmptyCover,
+    /// The minimized cover is not equivalent to the input, espresso fell back to the input cover.
+    VerificationFailed,
+    /// The C side could not find a PLA in the input.
+    Parse(String),
+    /// The input could not be converted into a PLA.
+    InvalidInput(String),
+    /// The temporary files used to exchange PLAs with the C side could not be used.
+    Io,
+    /// The worker process running the minimization died without reporting a result.
+    WorkerFailed(String),
+    /// Espresso aborted with a fatal error, e.g. on an inconsistent PLA header.
+    Fatal(String),
+}
+
+impl EspressoError {
+    /// Like [`EspressoError::from_status`], but takes the message of failed runs from their output.
+    pub(crate) fn from_output(
+        status: c_int,
+        output: Option<String>,
+    ) -> Result<String, EspressoError> {
+        match (status, output) {
+            (STATUS_WORKER_FAILED, Some(message)) => Err(EspressoError::WorkerFailed(message)),
+            (STATUS_FATAL, Some(message)) => Err(EspressoError::Fatal(message)),
+            (status, output) => {
+                EspressoError::from_status(status)?;
+                output.ok_or(EspressoError::Io)
+            }
+        }
+    }
+
+    pub(crate) fn from_status(status: c_int) -> Result<(), EspressoError> {
+        match status {
+            STATUS_OK => Ok(()),
+            STATUS_EMPTY_COVER => Err(EspressoError::EmptyCover),
+            STATUS_NO_PLA => Err(EspressoError::Parse(
+                "no PLA size (.i/.o or .mv) found in input".to_owned(),
+            )),
+            STATUS_VERIFY_FAILED => Err(EspressoError::VerificationFailed),
+            STATUS_IO => Err(EspressoError::Io),
+            STATUS_WORKER_FAILED => Err(EspressoError::WorkerFailed(
+                "terminated without a result".to_owned(),
+            )),
+            STATUS_FATAL => Err(EspressoError::Fatal("unknown error".to_owned())),
+            STATUS_SIZE_MISMATCH => Err(EspressoError::InvalidInput(
+                "the PLAs do not have the same variables".to_owned(),
+            )),
+            STATUS_BAD_RANGE => Err(EspressoError::InvalidInput(
+                "the variables are not part of the cube".to_owned(),
+            )),
+            _ => Err(EspressoError::Parse(format!("unknown status {}", status))),
+        }
+    }
+}
+
+impl Display for EspressoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EspressoError::MalformedPla { line, message } => {
+                write!(f, "malformed PLA in line {}: {}", line, message)
+            }
+            EspressoError::EmptyCover => write!(f, "the cover is empty"),
+            EspressoError::VerificationFailed => {
+                write!(f, "the minimized cover failed verification")
+            }
+            EspressoError::Parse(message) => {
+                write!(f, "espresso could not read the PLA: {}", message)
+            }
+            EspressoError::InvalidInput(message) => write!(f, "invalid input: {}", message),
+            EspressoError::Io => write!(f, "could not exchange the PLA with espresso"),
+            EspressoError::WorkerFailed(message) => {
+                write!(f, "the espresso worker process failed: {}", message)
+            }
+            EspressoError::Fatal(message) => write!(f, "espresso aborted: {}", message),
+        }
+    }
+}
+
+impl Error for EspressoError {}
@@ -0,0 +1,7 @@
+/*
+  Compiles cvrmisc.c without its fatal(), which exits the process.
+  The replacement reporting the error to Rust is defined in api.c.
+ */
+#undef fatal
+#define fatal espresso_fatal_unused
+#include "cvrmisc.c"
@@ -1,56 +1,499 @@
+//! Rust bindings for the espresso heuristic logic minimizer.
+//!
+//! # Thread safety
+//!
+//! The C core keeps its cube structure, set free lists and option flags in process globals.
+//! Every call into it is serialized by a crate-wide lock, so all functions of this crate may be
+//! called from multiple threads at once. Concurrent calls do not run in parallel though, use an
+//! [`EspressoPool`] to minimize several PLAs in parallel worker processes.
+
+use std::convert::TryFrom;
 use std::ffi::CStr;
-use std::mem::MaybeUninit;
-use std::os::raw::{c_char, c_uint, c_void};
+use std::ops::Range;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::ptr;
+use std::sync::{Mutex, MutexGuard};
 
-use pla_binary::PlaBinary;
-use rustsat::instances::Cnf;
+use error::{STATUS_BAD_RANGE, STATUS_VERIFY_FAILED};
+use options::RawOptions;
+use rustsat::{instances::Cnf, types::Lit};
 
+mod covers;
+mod error;
 mod multi_compress;
 mod multi_reduce;
+mod options;
 mod pla;
 mod pla_binary;
+mod pla_multi_valued;
+mod pool;
+mod verify;
 
-pub use multi_compress::espresso_compress;
-pub use multi_reduce::espresso_multi;
-use pla::PLA;
+pub use error::EspressoError;
+pub use multi_compress::{
+    canonicalize, column_itemizers, espresso_compress, espresso_compress_classes,
+    espresso_compress_forbidden, espresso_compress_with, try_espresso_compress,
+    try_espresso_compress_classes, try_espresso_compress_forbidden, try_espresso_compress_with,
+};
+pub use multi_reduce::{
+    espresso_multi, espresso_multi_with, try_espresso_multi, try_espresso_multi_with,
+};
+pub use options::{Algorithm, EspressoOptions};
+pub use pla::{OutputVal, PlaType, PLA};
+pub use pla_binary::PlaBinary;
+pub use pla_multi_valued::PlaMultiValued;
+pub use pool::{serve_worker, EspressoPool};
+pub use verify::{try_verify, verify, Counterexample};
 
 extern "C" {
-    fn run_espresso_from_data(data: *const c_char, l: c_uint, out: *mut *mut c_char);
+    fn run_espresso_from_data(
+        data: *const c_char,
+        l: c_uint,
+        opts: *const RawOptions,
+        out: *mut *mut c_char,
+    ) -> c_int;
+    fn run_d1merge_from_data(
+        data: *const c_char,
+        l: c_uint,
+        first: c_int,
+        last: c_int,
+        out: *mut *mut c_char,
+    ) -> c_int;
+    fn run_complement_from_data(
+        data: *const c_char,
+        l: c_uint,
+        opts: *const RawOptions,
+        out: *mut *mut c_char,
+    ) -> c_int;
+    fn run_primes_from_data(
+        data: *const c_char,
+        l: c_uint,
+        max_returned: c_int,
+        out: *mut *mut c_char,
+    ) -> c_int;
+    fn run_tautology_from_data(data: *const c_char, l: c_uint, out: *mut *mut c_char) -> c_int;
+    fn run_equiv_from_data(data: *const c_char, l: c_uint, out: *mut *mut c_char) -> c_int;
+    fn run_implies_from_data(
+        first: *const c_char,
+        first_length: c_uint,
+        second: *const c_char,
+        second_length: c_uint,
+        out: *mut *mut c_char,
+    ) -> c_int;
+    fn run_cover_op_from_data(
+        first: *const c_char,
+        first_length: c_uint,
+        second: *const c_char,
+        second_length: c_uint,
+        op: c_int,
+        out: *mut *mut c_char,
+    ) -> c_int;
     fn free(p: *mut c_void);
 }
 
-pub fn espresso<P: PLA>(pla: P) -> P {
-    let pla_string = pla.to_string();
+/// Guards the global state of the C core, every call into it has to hold this lock.
+static ESPRESSO_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_espresso() -> MutexGuard<'static, ()> {
+    // the C side never panics, so a poisoned lock still guards a consistent state
+    ESPRESSO_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Copies a string allocated by api.c and frees the original.
+fn take_c_string(buf: *mut c_char) -> Option<String> {
+    if buf.is_null() {
+        return None;
+    }
+    let result = unsafe { CStr::from_ptr(buf).to_string_lossy().into_owned() };
+    unsafe { free(buf as *mut c_void) };
+
+    Some(result)
+}
+
+/// Hands the PLA text to one of the `run_*_from_data` functions of api.c and collects the
+/// returned status and output (if any was produced).
+fn run_from_data<F>(pla_string: &str, run: F) -> (c_int, Option<String>)
+where
+    F: FnOnce(*const c_char, c_uint, *mut *mut c_char) -> c_int,
+{
     let bytes = pla_string.as_bytes();
+    let _guard = lock_espresso();
 
-    let mut buf = MaybeUninit::<*mut c_char>::uninit();
-    unsafe {
-        run_espresso_from_data(
-            bytes.as_ptr() as *const c_char,
-            bytes.len() as u32,
-            buf.as_mut_ptr(),
-        );
+    let mut buf: *mut c_char = ptr::null_mut();
+    let status = run(
+        bytes.as_ptr() as *const c_char,
+        bytes.len() as c_uint,
+        &mut buf,
+    );
+
+    (status, take_c_string(buf))
+}
+
+/// Like [`run_from_data`], for the functions of api.c taking two PLAs.
+fn run_from_pair<F>(first: &str, second: &str, run: F) -> (c_int, Option<String>)
+where
+    F: FnOnce(*const c_char, c_uint, *const c_char, c_uint, *mut *mut c_char) -> c_int,
+{
+    let _guard = lock_espresso();
+
+    let mut buf: *mut c_char = ptr::null_mut();
+    let status = run(
+        first.as_ptr() as *const c_char,
+        first.len() as c_uint,
+        second.as_ptr() as *const c_char,
+        second.len() as c_uint,
+        &mut buf,
+    );
+
+    (status, take_c_string(buf))
+}
+
+/// Minimizes the PLA text, returning the resulting PLA text and whether it passed verification.
+/// If verification failed, the result is the unminimized input cover.
+pub(crate) fn minimize_string(
+    pla_string: &str,
+    options: &EspressoOptions,
+) -> Result<(String, bool), EspressoError> {
+    let raw_options = options.to_raw();
+    let (status, result) = run_from_data(pla_string, |data, l, out| unsafe {
+        run_espresso_from_data(data, l, &raw_options, out)
+    });
+
+    minimize_result(status, result)
+}
+
+/// Interprets the status and output of a minimization run, see [`minimize_string`].
+fn minimize_result(status: c_int, result: Option<String>) -> Result<(String, bool), EspressoError> {
+    if status == STATUS_VERIFY_FAILED {
+        return Ok((result.ok_or(EspressoError::Io)?, false));
+    }
+
+    Ok((EspressoError::from_output(status, result)?, true))
+}
+
+pub fn espresso<P: PLA>(pla: P) -> P {
+    espresso_with(pla, &EspressoOptions::default())
+}
+
+/// Like [`espresso`], but reports malformed input, empty covers and failed verification as
+/// errors instead of panicking or silently returning the input cover.
+pub fn try_espresso<P: PLA>(pla: P) -> Result<P, EspressoError> {
+    try_espresso_with(pla, &EspressoOptions::default())
+}
+
+/// Runs [`espresso`] with the given options instead of the defaults.
+pub fn espresso_with<P: PLA>(pla: P, options: &EspressoOptions) -> P {
+    let (result, _) = minimize(&pla, options).unwrap_or_else(|e| panic!("espresso failed: {}", e));
+
+    result
+}
+
+/// Runs [`try_espresso`] with the given options instead of the defaults.
+pub fn try_espresso_with<P: PLA>(pla: P, options: &EspressoOptions) -> Result<P, EspressoError> {
+    let (result, verified) = minimize(&pla, options)?;
+    if !verified {
+        return Err(EspressoError::VerificationFailed);
+    }
+
+    Ok(result)
+}
+
+/// The result of [`try_espresso_minimized`].
+#[derive(Debug, Clone)]
+pub struct Minimized<P> {
+    /// The minimized PLA, or the input cover if `fell_back` is set.
+    pub pla: P,
+    /// Whether the minimized cover failed verification and espresso fell back to the input
+    /// cover, like [`espresso_with`] does silently.
+    pub fell_back: bool,
+}
+
+/// Runs [`espresso_with`], but reports whether espresso fell back to the input cover instead
+/// of hiding it like [`espresso_with`] or failing like [`try_espresso_with`].
+pub fn try_espresso_minimized<P: PLA>(
+    pla: P,
+    options: &EspressoOptions,
+) -> Result<Minimized<P>, EspressoError> {
+    let (pla, verified) = minimize(&pla, options)?;
+
+    Ok(Minimized {
+        pla,
+        fell_back: !verified,
+    })
+}
+
+/// Minimizes the PLA, directly in espresso's cube representation if the type supports it and
+/// through PLA text otherwise. Returns whether the result passed verification.
+pub(crate) fn minimize<P: PLA>(
+    pla: &P,
+    options: &EspressoOptions,
+) -> Result<(P, bool), EspressoError> {
+    // the covers only carry the ON-set back
+    let covers = match options.output_type {
+        PlaType::F => pla.to_covers(),
+        _ => None,
+    };
+    if let Some(covers) = covers {
+        let (result, verified) = covers::minimize(&covers?, options)?;
+        if let Some(result) = pla.with_covers(result) {
+            return Ok((result, verified));
+        }
+    }
+
+    let (result, verified) = minimize_string(&pla.to_string(), options)?;
+    Ok((P::parse(&result)?, verified))
+}
+
+/// Merges cubes that differ only in a single variable (espresso `-Dd1merge`).
+///
+/// The merge is applied to each variable in `vars` in turn. Variables are numbered as in the
+/// espresso cube: the binary inputs come first, the output part is the last variable.
+/// No full minimization is run, so this is much cheaper than [`espresso`].
+///
+/// Panics if `vars` is reversed or reaches past the output part, see [`try_d1merge`].
+pub fn d1merge<P: PLA>(pla: P, vars: Range<usize>) -> P {
+    try_d1merge(pla, vars).unwrap_or_else(|e| panic!("d1merge failed: {}", e))
+}
+
+/// Like [`d1merge`], but returns an error instead of panicking. A reversed range, or one that is
+/// not within the variables of the cube, is reported as [`EspressoError::InvalidInput`]. An empty
+/// range within the cube returns the PLA unchanged, as an ON-set like any other range.
+pub fn try_d1merge<P: PLA>(pla: P, vars: Range<usize>) -> Result<P, EspressoError> {
+    let bad_range = || {
+        EspressoError::InvalidInput(format!(
+            "variables {}..{} are not a range within the cube",
+            vars.start, vars.end
+        ))
+    };
+    let first = c_int::try_from(vars.start).map_err(|_| bad_range())?;
+    let end = c_int::try_from(vars.end).map_err(|_| bad_range())?;
+
+    let (status, result) = run_from_data(&pla.to_string(), |data, l, out| unsafe {
+        run_d1merge_from_data(data, l, first, end - 1, out)
+    });
+    if status == STATUS_BAD_RANGE {
+        return Err(bad_range());
+    }
+    let result = EspressoError::from_output(status, result)?;
+
+    P::parse(&result)
+}
+
+/// The operations on two covers of api.c, keep in sync with the defines there.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum CoverOp {
+    Union = 0,
+    Intersect = 1,
+    Sharp = 2,
+    DisjointSharp = 3,
+    Xor = 4,
+}
+
+/// Applies the operation to the ON-sets of both PLAs, the result has the labels of `first`.
+pub(crate) fn combine<P: PLA>(first: &P, second: &P, op: CoverOp) -> Result<P, EspressoError> {
+    let (status, result) = run_from_pair(
+        &first.to_string(),
+        &second.to_string(),
+        |first, first_length, second, second_length, out| unsafe {
+            run_cover_op_from_data(first, first_length, second, second_length, op as c_int, out)
+        },
+    );
+    let result = EspressoError::from_output(status, result)?;
+
+    P::parse(&result)
+}
+
+/// Computes the OFF-set of the PLA as a new ON-set, minimized with `options` if given. See
+/// [`PlaBinary::complement`].
+pub(crate) fn complement<P: PLA>(
+    pla: &P,
+    options: Option<&EspressoOptions>,
+) -> Result<P, EspressoError> {
+    let raw_options = options.map(EspressoOptions::to_raw);
+    let opts = raw_options
+        .as_ref()
+        .map_or(ptr::null(), |o| o as *const RawOptions);
+    let (status, result) = run_from_data(&pla.to_string(), |data, l, out| unsafe {
+        run_complement_from_data(data, l, opts, out)
+    });
+
+    let (result, verified) = minimize_result(status, result)?;
+    if !verified {
+        return Err(EspressoError::VerificationFailed);
+    }
+
+    P::parse(&result)
+}
+
+/// Generates the primes of the ON-set and don't-care set, see [`PlaBinary::primes`].
+pub(crate) fn primes<P: PLA>(pla: &P, max_returned: Option<usize>) -> Result<P, EspressoError> {
+    let max_returned = max_returned.map_or(-1, |m| m.min(c_int::MAX as usize) as c_int);
+    let (status, result) = run_from_data(&pla.to_string(), |data, l, out| unsafe {
+        run_primes_from_data(data, l, max_returned, out)
+    });
+    let result = EspressoError::from_output(status, result)?;
+
+    P::parse(&result)
+}
+
+/// Whether the ON-set and don't-care set of the PLA cover every minterm, see
+/// [`PlaBinary::is_tautology`].
+pub(crate) fn is_tautology<P: PLA>(pla: &P) -> Result<bool, EspressoError> {
+    let (status, result) = run_from_data(&pla.to_string(), |data, l, out| unsafe {
+        run_tautology_from_data(data, l, out)
+    });
+
+    query_result(status, result)
+}
+
+/// Whether the ON-set of `first` is covered by the ON-set and don't-care set of `second`, see
+/// [`PlaBinary::implies`].
+pub(crate) fn implies<P: PLA>(first: &P, second: &P) -> Result<bool, EspressoError> {
+    let (status, result) = run_from_pair(
+        &first.to_string(),
+        &second.to_string(),
+        |first, first_length, second, second_length, out| unsafe {
+            run_implies_from_data(first, first_length, second, second_length, out)
+        },
+    );
+
+    query_result(status, result)
+}
+
+/// Two outputs of a PLA that describe the same function, see [`PlaBinary::equivalent_outputs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EquivalentOutputs {
+    /// The index of the first output.
+    pub first: usize,
+    /// The index of the second output, always greater than `first`.
+    pub second: usize,
+    /// Whether the first output has to be negated.
+    pub first_negated: bool,
+    /// Whether the second output has to be negated.
+    pub second_negated: bool,
+}
+
+impl EquivalentOutputs {
+    fn parse(line: &str) -> Option<EquivalentOutputs> {
+        let values = line
+            .split_whitespace()
+            .map(|v| v.parse::<usize>().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        match values[..] {
+            [first, second, first_negated, second_negated] => Some(EquivalentOutputs {
+                first,
+                second,
+                first_negated: first_negated == 1,
+                second_negated: second_negated == 1,
+            }),
+            _ => None,
+        }
     }
-    let c_str = Box::new(unsafe { buf.assume_init() });
-    let result = unsafe { CStr::from_ptr(*c_str).to_str().unwrap().to_owned() };
-    unsafe { free(*c_str as *mut c_void) };
+}
 
-    P::from(result)
+/// All pairs of equivalent outputs of the PLA, see [`PlaBinary::equivalent_outputs`].
+pub(crate) fn equivalent_outputs<P: PLA>(pla: &P) -> Result<Vec<EquivalentOutputs>, EspressoError> {
+    let (status, result) = run_from_data(&pla.to_string(), |data, l, out| unsafe {
+        run_equiv_from_data(data, l, out)
+    });
+
+    EspressoError::from_output(status, result)?
+        .lines()
+        .map(|line| {
+            EquivalentOutputs::parse(line)
+                .ok_or_else(|| EspressoError::Parse(format!("bad equivalence \"{}\"", line)))
+        })
+        .collect()
 }
 
-pub fn espresso_cnf(cnf: Cnf, max_id: u32) -> Cnf {
-    let pla = PlaBinary::from_cnf(cnf, max_id);
+/// Reads the answer of the queries of api.c, which write 1 or 0.
+fn query_result(status: c_int, result: Option<String>) -> Result<bool, EspressoError> {
+    match EspressoError::from_output(status, result)?.trim() {
+        "1" => Ok(true),
+        "0" => Ok(false),
+        answer => Err(EspressoError::Parse(format!("bad answer \"{}\"", answer))),
+    }
+}
+
+/// Minimizes a CNF by minimizing the DNF of its negation.
+///
+/// Only the variables used in the CNF become columns of the PLA, so sparse variable indices
+/// do not blow up the rows. A CNF without clauses (or with only tautological ones) is returned
+/// as an empty CNF.
+pub fn espresso_cnf(cnf: Cnf) -> Cnf {
+    let (cnf, vars) = compact_cnf(cnf);
+    let pla = PlaBinary::from_cnf(cnf, vars.len() as u32);
+    // espresso rejects an empty cover, but the CNF is already minimal
+    if pla.rows().next().is_none() {
+        return Cnf::new();
+    }
 
     let result = espresso(pla);
 
-    return result.to_cnf();
+    expand_cnf(result.to_cnf(), &vars)
+}
+
+/// Like [`espresso_cnf`], but reports failed verification as an error.
+pub fn try_espresso_cnf(cnf: Cnf) -> Result<Cnf, EspressoError> {
+    let (cnf, vars) = compact_cnf(cnf);
+    let pla = PlaBinary::try_from_cnf(cnf, vars.len() as u32)?;
+    if pla.rows().next().is_none() {
+        return Ok(Cnf::new());
+    }
+
+    let result = try_espresso(pla)?;
+
+    Ok(expand_cnf(result.to_cnf(), &vars))
+}
+
+/// Renumbers the variables of the CNF to `0..n` in the order of their indices, returns the
+/// renumbered CNF and the original index of every new one.
+fn compact_cnf(cnf: Cnf) -> (Cnf, Vec<u32>) {
+    let mut vars: Vec<u32> = cnf
+        .iter()
+        .flat_map(|clause| clause.iter().map(|lit| lit.vidx32()))
+        .collect();
+    vars.sort_unstable();
+    vars.dedup();
+
+    let cnf = cnf
+        .into_iter()
+        .map(|clause| {
+            clause
+                .into_iter()
+                .map(|lit| {
+                    let var = vars.binary_search(&lit.vidx32()).unwrap();
+                    Lit::new(var as u32, lit.is_neg())
+                })
+                .collect()
+        })
+        .collect();
+
+    (cnf, vars)
+}
+
+/// Reverts [`compact_cnf`].
+fn expand_cnf(cnf: Cnf, vars: &[u32]) -> Cnf {
+    cnf.into_iter()
+        .map(|clause| {
+            clause
+                .into_iter()
+                .map(|lit| Lit::new(vars[lit.vidx()], lit.is_neg()))
+                .collect()
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use rustsat::types::TernaryVal;
 
-    use crate::{espresso, pla_binary::PlaBinary};
+    use crate::{
+        d1merge, espresso, espresso_with, minimize_string, pla_binary::PlaBinary, try_d1merge,
+        try_espresso, try_espresso_minimized, try_espresso_with, verify, Algorithm, EspressoError,
+        EspressoOptions, PlaType, PLA,
+    };
 
     #[test]
     fn test_espresso() {
@@ -127,4 +570,269 @@ mod tests {
         println!("{:?}", result);
         assert!(false);
     }
+
+    #[test]
+    fn test_d1merge() {
+        let pla = || {
+            let mut pla = PlaBinary::default();
+
+            pla.add_line(
+                vec![TernaryVal::False, TernaryVal::True, TernaryVal::False],
+                vec![TernaryVal::True],
+            );
+            pla.add_line(
+                vec![TernaryVal::False, TernaryVal::True, TernaryVal::True],
+                vec![TernaryVal::True],
+            );
+            pla
+        };
+
+        let cubes = |pla: PlaBinary| {
+            pla.to_string()
+                .lines()
+                .filter(|l| !l.starts_with('.'))
+                .map(|l| l.to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        // the cubes only differ in the third variable
+        assert_eq!(cubes(d1merge(pla(), 0..2)).len(), 2);
+        assert_eq!(cubes(d1merge(pla(), 2..3)), vec!["01- 1"]);
+        // the output is variable 3, anything past it is not a variable of the cube
+        assert!(matches!(
+            try_d1merge(pla(), 5..7),
+            Err(EspressoError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            try_d1merge(pla(), 2..5),
+            Err(EspressoError::InvalidInput(_))
+        ));
+        // reversed and empty ranges are checked like any other
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 3..2;
+        assert!(matches!(
+            try_d1merge(pla(), reversed),
+            Err(EspressoError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            try_d1merge(pla(), 9..9),
+            Err(EspressoError::InvalidInput(_))
+        ));
+        let unchanged = try_d1merge(pla(), 2..2).unwrap();
+        assert_eq!(unchanged.pla_type(), PlaType::F);
+        assert_eq!(cubes(unchanged).len(), 2);
+        assert_eq!(cubes(d1merge(pla(), 0..0)).len(), 2);
+    }
+
+    #[test]
+    fn test_try_espresso_empty() {
+        let result = try_espresso(PlaBinary::default());
+        assert_eq!(result.unwrap_err(), EspressoError::EmptyCover);
+
+        // the failed call must not leave espresso's global state behind
+        let mut pla = PlaBinary::default();
+        pla.add_line(
+            vec![TernaryVal::False, TernaryVal::True],
+            vec![TernaryVal::True],
+        );
+        pla.add_line(
+            vec![TernaryVal::True, TernaryVal::True],
+            vec![TernaryVal::True],
+        );
+        let result = try_espresso(pla).unwrap();
+        assert_eq!(result.to_string(), ".i 2\n.o 1\n.type f\n-1 1\n.e\n");
+    }
+
+    #[test]
+    fn test_espresso_with_options() {
+        let and = || {
+            let mut pla = PlaBinary::default();
+            pla.add_line(
+                vec![TernaryVal::True, TernaryVal::True],
+                vec![TernaryVal::True],
+            );
+            pla
+        };
+
+        let fast = EspressoOptions::default().single_expand(true);
+        assert_eq!(
+            espresso_with(and(), &fast).to_string(),
+            ".i 2\n.o 1\n.type f\n11 1\n.e\n"
+        );
+
+        // the complement of x0 & x1 is !x0 | !x1
+        let pos = EspressoOptions::default().pos(true);
+        let result = espresso_with(and(), &pos).to_string();
+        assert!(result.contains("\n0- 1\n"));
+        assert!(result.contains("\n-0 1\n"));
+
+        // the options must not leak into the next run
+        assert_eq!(
+            espresso(and()).to_string(),
+            ".i 2\n.o 1\n.type f\n11 1\n.e\n"
+        );
+    }
+
+    #[test]
+    fn test_espresso_output_type() {
+        // the don't-care set of an fr PLA is everything neither on nor off
+        let pla = || PlaBinary::parse(".i 2\n.o 1\n.type fr\n00 1\n01 0\n.e\n").unwrap();
+
+        let with_type = |output_type| {
+            let options = EspressoOptions::default().output_type(output_type);
+            espresso_with(pla(), &options).to_string()
+        };
+        assert_eq!(with_type(PlaType::F), ".i 2\n.o 1\n.type f\n-0 1\n.e\n");
+        assert_eq!(
+            with_type(PlaType::Fd),
+            ".i 2\n.o 1\n.type fd\n-0 1\n1- -\n.e\n"
+        );
+        assert_eq!(
+            with_type(PlaType::Fdr),
+            ".i 2\n.o 1\n.type fdr\n-0 1\n1- -\n01 0\n.e\n"
+        );
+    }
+
+    #[test]
+    fn test_try_espresso_minimized() {
+        let pla = || PlaBinary::parse(".i 2\n.o 1\n00 1\n01 1\n.e\n").unwrap();
+
+        let result = try_espresso_minimized(pla(), &EspressoOptions::default()).unwrap();
+
+        assert!(!result.fell_back);
+        assert_eq!(result.pla.to_string(), ".i 2\n.o 1\n.type f\n0- 1\n.e\n");
+        assert_eq!(verify(&pla(), &result.pla), Ok(()));
+    }
+
+    #[test]
+    fn test_espresso_canonical() {
+        let pla = || {
+            let mut pla = PlaBinary::default();
+            for inputs in ["0000", "0011", "1100", "1111", "0101"] {
+                pla.add_line(
+                    inputs.chars().map(|c| TernaryVal::from(c == '1')).collect(),
+                    vec![TernaryVal::True],
+                );
+            }
+            pla
+        };
+
+        // the algorithms find the same cubes, but in a different order
+        let canonical = EspressoOptions::default().canonical(true);
+        let exact = canonical.clone().algorithm(Algorithm::Exact);
+        assert_eq!(
+            espresso_with(pla(), &canonical).to_string(),
+            espresso_with(pla(), &exact).to_string()
+        );
+    }
+
+    #[test]
+    fn test_espresso_exact() {
+        // x0 x1 + !x0 x2 + x1 x2, the consensus term x1 x2 is redundant
+        let mut pla = PlaBinary::default();
+        for inputs in ["110", "111", "001", "011"] {
+            pla.add_line(
+                inputs.chars().map(|c| TernaryVal::from(c == '1')).collect(),
+                vec![TernaryVal::True],
+            );
+        }
+        let pla_string = pla.to_string();
+
+        for algorithm in [
+            Algorithm::Exact,
+            Algorithm::ExactLiterals,
+            Algorithm::Signature,
+        ] {
+            let options = EspressoOptions::default().algorithm(algorithm);
+            let result = try_espresso_with(PlaBinary::from(pla_string.clone()), &options)
+                .unwrap()
+                .to_string();
+
+            assert!(result.contains("\n11- 1\n"));
+            assert!(result.contains("\n0-1 1\n"));
+            assert_eq!(result.lines().filter(|l| !l.starts_with('.')).count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_espresso_single_output() {
+        // f0 = x0 x1, f1 = x0 x1 + !x0, espresso shares the cube x0 x1 between the outputs
+        let mut pla = PlaBinary::default();
+        for (inputs, outputs) in [("11", "11"), ("00", "01"), ("01", "01")] {
+            pla.add_line(
+                inputs.chars().map(|c| TernaryVal::from(c == '1')).collect(),
+                outputs
+                    .chars()
+                    .map(|c| TernaryVal::from(c == '1'))
+                    .collect(),
+            );
+        }
+        let pla_string = pla.to_string();
+
+        let shared = espresso(PlaBinary::from(pla_string.clone())).to_string();
+        assert!(shared.contains(" 11\n"));
+
+        let options = EspressoOptions::default().algorithm(Algorithm::SingleOutput);
+        let single = try_espresso_with(PlaBinary::from(pla_string.clone()), &options)
+            .unwrap()
+            .to_string();
+        assert!(!single.contains(" 11\n"));
+
+        let options = EspressoOptions::default().algorithm(Algorithm::Simplify);
+        assert!(try_espresso_with(PlaBinary::from(pla_string), &options).is_ok());
+    }
+
+    #[test]
+    fn test_espresso_threads() {
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    (0..20)
+                        .map(|_| {
+                            let mut pla = PlaBinary::default();
+                            pla.add_line(
+                                vec![TernaryVal::from(i % 2 == 0), TernaryVal::True],
+                                vec![TernaryVal::True],
+                            );
+                            pla.add_line(
+                                vec![TernaryVal::from(i % 2 == 0), TernaryVal::False],
+                                vec![TernaryVal::True],
+                            );
+                            espresso(pla).to_string()
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            let expected = if i % 2 == 0 { "1- 1" } else { "0- 1" };
+            for result in handle.join().unwrap() {
+                assert_eq!(result, format!(".i 2\n.o 1\n.type f\n{}\n.e\n", expected));
+            }
+        }
+    }
+
+    #[test]
+    fn test_espresso_fatal() {
+        let result = minimize_string(
+            ".i 2\n.o 1\n.type q\n11 1\n.e\n",
+            &EspressoOptions::default(),
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            EspressoError::Fatal("unknown type in .type command".to_owned())
+        );
+
+        // the process survives and the next run starts from a clean state
+        let mut pla = PlaBinary::default();
+        pla.add_line(
+            vec![TernaryVal::True, TernaryVal::True],
+            vec![TernaryVal::True],
+        );
+        assert_eq!(
+            try_espresso(pla).unwrap().to_string(),
+            ".i 2\n.o 1\n.type f\n11 1\n.e\n"
+        );
+    }
 }
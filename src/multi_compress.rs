@@ -1,143 +1,416 @@
-use std::{
-    ffi::{c_char, CStr},
-    mem::MaybeUninit,
-    os::raw::c_void,
-};
+use std::hash::Hash;
 
 use itemizer::{Item, Itemizer};
-use ndarray::{Array2, AssignElem, Axis};
-use std::fmt::Write;
+use ndarray::{Array2, ArrayView1, Axis};
 
-use crate::{free, run_espresso_from_data};
+use crate::{error::EspressoError, minimize, EspressoOptions, OutputVal, PlaMultiValued, PlaType};
 
-/// /// Compresses a matrix of optional string vectors using the Espresso algorithm.
+/// A compressed matrix, see [`espresso_compress`].
+type Table<T> = Array2<Option<Vec<T>>>;
+
+/// Compresses a matrix of optional value vectors using the Espresso algorithm.
 ///
-/// This function takes a 2D array (matrix) where each cell can contain an optional vector of strings,
-/// and a vector of `Itemizer` instances representing the variables in each column. It then uses the
-/// Espresso algorithm to compress the matrix, reducing the number of rows while preserving the
+/// This function takes a 2D array (matrix) where each cell can contain an optional vector of values,
+/// and optionally the `Itemizer` instances representing the variables in each column. It then uses
+/// the Espresso algorithm to compress the matrix, reducing the number of rows while preserving the
 /// essential information.
 ///
 /// # Arguments
 ///
-/// * `matrix` - A reference to a 2D array of `Option<Vec<String>>`. Each cell represents a set of
+/// * `matrix` - A reference to a 2D array of `Option<Vec<T>>`. Each cell represents a set of
 ///   possible values for a variable. `None` indicates that any value is possible.
-/// * `variables` - A reference to a vector of `Itemizer<String>`. Each `Itemizer` corresponds to a
-///   column in the matrix and maps strings to unique IDs.
+/// * `variables` - The `Itemizer` of each column, mapping values to unique IDs. If `None`, they
+///   are built from the values occurring in the matrix, see [`column_itemizers`].
 ///
 /// # Returns
 ///
-/// A new 2D array of `Option<Vec<String>>` representing the compressed matrix.
-
-pub fn espresso_compress(
-    matrix: &Array2<Option<Vec<String>>>,
-    variables: &Vec<Itemizer<String>>,
-) -> Array2<Option<Vec<String>>> {
-    let mut pla_string = String::new();
-
-    writeln!(
-        pla_string,
-        ".mv {} 0 {} 2",
-        matrix.dim().1 + 1,
-        variables
-            .iter()
-            .map(|x| x.len().to_string())
-            .collect::<Vec<String>>()
-            .join(" ")
-    )
-    .unwrap();
-
-    writeln!(pla_string, ".ob ON OFF").unwrap();
-    writeln!(pla_string, ".p {}", matrix.dim().0).unwrap();
-    writeln!(pla_string, ".type f").unwrap();
-
-    for row in matrix.axis_iter(Axis(0)) {
-        for (i, var) in row.iter().zip(variables) {
-            if let Some(i) = i {
-                let mut s = std::iter::repeat("0")
-                    .take(var.len() - 1)
-                    .collect::<String>();
-                for x in i {
-                    let id = var.id_of_opt(x).unwrap().as_index();
-
-                    s.insert(id, '1');
-                }
-                write!(pla_string, "{}", s).unwrap()
-            } else {
-                for _ in 0..var.len() {
-                    write!(pla_string, "1").unwrap()
-                }
-            }
-            write!(pla_string, "|").unwrap();
-        }
+/// A new 2D array of `Option<Vec<T>>` representing the compressed matrix.
+pub fn espresso_compress<T: Hash + Eq + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    variables: Option<&[Itemizer<T>]>,
+) -> Array2<Option<Vec<T>>> {
+    compress(matrix, variables, &EspressoOptions::default(), false)
+        .unwrap_or_else(|e| panic!("espresso failed: {}", e))
+}
+
+/// Like [`espresso_compress`], but reports values missing from the itemizers and failed
+/// verification as errors instead of panicking.
+pub fn try_espresso_compress<T: Hash + Eq + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    variables: Option<&[Itemizer<T>]>,
+) -> Result<Array2<Option<Vec<T>>>, EspressoError> {
+    compress(matrix, variables, &EspressoOptions::default(), true)
+}
+
+/// Like [`espresso_compress`], but minimizes with the given options. With
+/// [`EspressoOptions::canonical`] the result is passed through [`canonicalize`]. The output type
+/// of the options is ignored, the result only holds the ON-set.
+pub fn espresso_compress_with<T: Hash + Ord + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    variables: Option<&[Itemizer<T>]>,
+    options: &EspressoOptions,
+) -> Array2<Option<Vec<T>>> {
+    let result = compress(matrix, variables, options, false)
+        .unwrap_or_else(|e| panic!("espresso failed: {}", e));
+    canonicalize_with(result, options)
+}
+
+/// Like [`espresso_compress_with`], but reports errors like [`try_espresso_compress`].
+pub fn try_espresso_compress_with<T: Hash + Ord + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    variables: Option<&[Itemizer<T>]>,
+    options: &EspressoOptions,
+) -> Result<Array2<Option<Vec<T>>>, EspressoError> {
+    let result = compress(matrix, variables, options, true)?;
+    Ok(canonicalize_with(result, options))
+}
 
-        writeln!(pla_string, "10").unwrap();
+/// Builds one `Itemizer` per column of the matrix, holding the values occurring in it.
+pub fn column_itemizers<T: Hash + Eq + Clone>(matrix: &Array2<Option<Vec<T>>>) -> Vec<Itemizer<T>> {
+    let mut itemizers: Vec<_> = (0..matrix.ncols()).map(|_| Itemizer::new()).collect();
+    add_values(&mut itemizers, matrix);
+    itemizers
+}
+
+fn add_values<T: Hash + Eq + Clone>(
+    itemizers: &mut [Itemizer<T>],
+    matrix: &Array2<Option<Vec<T>>>,
+) {
+    for (itemizer, column) in itemizers.iter_mut().zip(matrix.axis_iter(Axis(1))) {
+        for x in column.iter().flatten().flatten() {
+            itemizer.id_of(x);
+        }
     }
+}
 
-    writeln!(pla_string, ".e").unwrap();
+/// Like [`espresso_compress`], but with negative examples: no row of the result covers a
+/// combination of `forbidden`, any combination in neither matrix may be covered.
+///
+/// Everything not forbidden is a don't-care (`.type fr`), so the result generalizes over the
+/// gaps between the rows of the matrix as far as possible. If `variables` is `None`, the
+/// itemizers are built from the values of both matrices.
+pub fn espresso_compress_forbidden<T: Hash + Eq + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    forbidden: &Array2<Option<Vec<T>>>,
+    variables: Option<&[Itemizer<T>]>,
+) -> Array2<Option<Vec<T>>> {
+    compress_forbidden(matrix, forbidden, variables, false)
+        .unwrap_or_else(|e| panic!("espresso failed: {}", e))
+}
 
-    let bytes = pla_string.as_bytes();
-    let mut buf = MaybeUninit::<*mut c_char>::uninit();
-    unsafe {
-        run_espresso_from_data(
-            bytes.as_ptr() as *const c_char,
-            bytes.len() as u32,
-            buf.as_mut_ptr(),
-        );
+/// Like [`espresso_compress_forbidden`], but reports errors like [`try_espresso_compress`].
+pub fn try_espresso_compress_forbidden<T: Hash + Eq + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    forbidden: &Array2<Option<Vec<T>>>,
+    variables: Option<&[Itemizer<T>]>,
+) -> Result<Array2<Option<Vec<T>>>, EspressoError> {
+    compress_forbidden(matrix, forbidden, variables, true)
+}
+
+/// Sorts the values of every cell and then the rows of a compressed table, so equal tables
+/// come out identical regardless of the order espresso and the itemizers produced them in.
+pub fn canonicalize<T: Ord + Clone>(table: &Array2<Option<Vec<T>>>) -> Array2<Option<Vec<T>>> {
+    let mut rows: Vec<Vec<Option<Vec<T>>>> = table
+        .rows()
+        .into_iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| {
+                    cell.clone().map(|mut values| {
+                        values.sort();
+                        values
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    rows.sort();
+
+    Array2::from_shape_vec(table.dim(), rows.into_iter().flatten().collect())
+        .expect("the rows keep their length")
+}
+
+/// Canonicalizes the table if the options ask for a canonical result.
+pub(crate) fn canonicalize_with<T: Ord + Clone>(
+    table: Array2<Option<Vec<T>>>,
+    options: &EspressoOptions,
+) -> Array2<Option<Vec<T>>> {
+    if options.canonical {
+        canonicalize(&table)
+    } else {
+        table
     }
-    let c_str = Box::new(unsafe { buf.assume_init() });
-    let result = unsafe { CStr::from_ptr(*c_str).to_str().unwrap().to_owned() };
-    unsafe { free(*c_str as *mut c_void) };
+}
+
+/// Like [`espresso_compress`], but for a decision table: `classes` holds the class of each row
+/// of the matrix, e.g. the action to take.
+///
+/// The class is encoded as a multi-valued output, so all classes are minimized in one pass and
+/// cubes are shared between classes where possible. Returns the compressed rows of every class,
+/// in the order the classes first occur in `classes`.
+pub fn espresso_compress_classes<T: Hash + Eq + Clone, C: Hash + Eq + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    classes: &[C],
+    variables: Option<&[Itemizer<T>]>,
+) -> Vec<(C, Array2<Option<Vec<T>>>)> {
+    compress_classes(matrix, classes, variables, false)
+        .unwrap_or_else(|e| panic!("espresso failed: {}", e))
+}
 
-    let mut array = MaybeUninit::<Array2<Option<Vec<String>>>>::zeroed();
-    let mut row_position = 0;
+/// Like [`espresso_compress_classes`], but reports errors like [`try_espresso_compress`].
+pub fn try_espresso_compress_classes<T: Hash + Eq + Clone, C: Hash + Eq + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    classes: &[C],
+    variables: Option<&[Itemizer<T>]>,
+) -> Result<Vec<(C, Table<T>)>, EspressoError> {
+    compress_classes(matrix, classes, variables, true)
+}
 
-    for l in result.lines() {
-        if l.starts_with(".e") {
-            break;
+fn compress<T: Hash + Eq + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    variables: Option<&[Itemizer<T>]>,
+    options: &EspressoOptions,
+    strict: bool,
+) -> Result<Array2<Option<Vec<T>>>, EspressoError> {
+    let built;
+    let variables = match variables {
+        Some(variables) => variables,
+        None => {
+            built = column_itemizers(matrix);
+            &built
         }
-        if l.starts_with(".p") {
-            let len = l[3..l.len()].parse::<usize>().unwrap();
+    };
+    let outputs = vec![vec![OutputVal::On, OutputVal::Off]; matrix.nrows()];
+    let result = minimize_table(matrix, None, variables, outputs, options, strict)?;
 
-            array.assign_elem(Array2::default((len, variables.len())));
-            continue;
+    Ok(to_array(&result, variables, |_| true))
+}
+
+fn compress_forbidden<T: Hash + Eq + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    forbidden: &Array2<Option<Vec<T>>>,
+    variables: Option<&[Itemizer<T>]>,
+    strict: bool,
+) -> Result<Array2<Option<Vec<T>>>, EspressoError> {
+    let mut built;
+    let variables = match variables {
+        Some(variables) => variables,
+        None => {
+            built = column_itemizers(matrix);
+            add_values(&mut built, forbidden);
+            &built
+        }
+    };
+    let outputs = vec![vec![OutputVal::On, OutputVal::Off]; matrix.nrows()];
+    let result = minimize_table(
+        matrix,
+        Some(forbidden),
+        variables,
+        outputs,
+        &EspressoOptions::default(),
+        strict,
+    )?;
+
+    // with the off-set given, the OFF output is a don't-care and may be set in the result
+    Ok(to_array(&result, variables, |outputs| {
+        outputs[0] == OutputVal::On
+    }))
+}
+
+fn compress_classes<T: Hash + Eq + Clone, C: Hash + Eq + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    classes: &[C],
+    variables: Option<&[Itemizer<T>]>,
+    strict: bool,
+) -> Result<Vec<(C, Table<T>)>, EspressoError> {
+    if classes.len() != matrix.nrows() {
+        return Err(EspressoError::InvalidInput(format!(
+            "{} classes for {} rows",
+            classes.len(),
+            matrix.nrows()
+        )));
+    }
+
+    let built;
+    let variables = match variables {
+        Some(variables) => variables,
+        None => {
+            built = column_itemizers(matrix);
+            &built
+        }
+    };
+    let mut class_itemizer = Itemizer::new();
+    let ids: Vec<usize> = classes
+        .iter()
+        .map(|c| class_itemizer.id_of(c).as_index())
+        .collect();
+    let outputs = ids
+        .iter()
+        .map(|&id| {
+            (0..class_itemizer.len())
+                .map(|c| {
+                    if c == id {
+                        OutputVal::On
+                    } else {
+                        OutputVal::Off
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let result = minimize_table(
+        matrix,
+        None,
+        variables,
+        outputs,
+        &EspressoOptions::default(),
+        strict,
+    )?;
+
+    Ok((0..class_itemizer.len())
+        .map(|c| {
+            let class = class_itemizer.value_of(&Item::with_id(c as u32)).clone();
+            let array = to_array(&result, variables, |outputs| outputs[c] == OutputVal::On);
+            (class, array)
+        })
+        .collect())
+}
+
+/// Encodes every row of the matrix followed by the given output part and minimizes the
+/// resulting PLA. The rows of `forbidden` make up the OFF-set of every output, if given.
+fn minimize_table<T: Hash + Eq + Clone>(
+    matrix: &Array2<Option<Vec<T>>>,
+    forbidden: Option<&Array2<Option<Vec<T>>>>,
+    variables: &[Itemizer<T>],
+    outputs: Vec<Vec<OutputVal>>,
+    options: &EspressoOptions,
+    strict: bool,
+) -> Result<PlaMultiValued, EspressoError> {
+    for m in std::iter::once(matrix).chain(forbidden) {
+        if variables.len() != m.ncols() {
+            return Err(EspressoError::InvalidInput(format!(
+                "{} itemizers for {} columns",
+                variables.len(),
+                m.ncols()
+            )));
         }
-        if l.starts_with(".") {
-            continue;
+    }
+
+    let num_outputs = outputs.first().map_or(0, Vec::len);
+    let mut sizes: Vec<usize> = variables.iter().map(|x| x.len()).collect();
+    sizes.push(num_outputs);
+    let pla_type = if forbidden.is_some() {
+        PlaType::Fr
+    } else {
+        PlaType::F
+    };
+    let mut pla = PlaMultiValued::new(0, sizes, pla_type);
+
+    for (row, outputs) in matrix.axis_iter(Axis(0)).zip(outputs) {
+        pla.add_row(encode_row(row, variables)?, outputs);
+    }
+    if let Some(forbidden) = forbidden {
+        for row in forbidden.axis_iter(Axis(0)) {
+            pla.add_row(
+                encode_row(row, variables)?,
+                vec![OutputVal::Off; num_outputs],
+            );
         }
+    }
 
-        let array = unsafe { array.assume_init_mut() };
+    // espresso reports a PLA without rows as an empty cover, which compresses to nothing
+    if matrix.nrows() == 0 {
+        return Ok(pla);
+    }
 
-        for (j, var) in l.split(' ').skip(1).enumerate() {
-            if j >= variables.len() {
-                break;
+    // only the ON-set is decoded into the table
+    let options = options.clone().output_type(PlaType::F);
+    let (result, verified) = minimize(&pla, &options)?;
+    if strict && !verified {
+        return Err(EspressoError::VerificationFailed);
+    }
+
+    Ok(result)
+}
+
+/// The parts of a row of the matrix, `None` allows all values of a column.
+pub(crate) fn encode_row<T: Hash + Eq + Clone>(
+    row: ArrayView1<Option<Vec<T>>>,
+    variables: &[Itemizer<T>],
+) -> Result<Vec<bool>, EspressoError> {
+    let mut parts = Vec::new();
+    for (j, (i, var)) in row.iter().zip(variables).enumerate() {
+        if let Some(i) = i {
+            let mut var_parts = vec![false; var.len()];
+            for x in i {
+                let id = var
+                    .id_of_opt(x)
+                    .ok_or_else(|| {
+                        EspressoError::InvalidInput(format!(
+                            "value missing from the itemizer of column {}",
+                            j
+                        ))
+                    })?
+                    .as_index();
+
+                var_parts[id] = true;
             }
-            if var.chars().all(|x| x == '1') {
+            parts.extend(var_parts);
+        } else {
+            parts.extend(vec![true; var.len()]);
+        }
+    }
+
+    Ok(parts)
+}
+
+/// Decodes the rows of the minimized PLA whose outputs are accepted by `filter`.
+fn to_array<T: Hash + Eq + Clone>(
+    result: &PlaMultiValued,
+    variables: &[Itemizer<T>],
+    filter: impl Fn(&[OutputVal]) -> bool,
+) -> Array2<Option<Vec<T>>> {
+    let rows: Vec<_> = result
+        .rows()
+        .filter(|(_, outputs)| filter(outputs))
+        .collect();
+    let mut array: Array2<Option<Vec<T>>> = Array2::default((rows.len(), variables.len()));
+
+    for (row_position, (parts, _)) in rows.into_iter().enumerate() {
+        for (j, var) in variables.iter().enumerate() {
+            let var_parts = &parts[result.var_parts(j)];
+            if var_parts.iter().all(|&p| p) {
                 continue;
             }
-            for (p, c) in var.chars().enumerate() {
-                if c == '1' {
-                    let val = variables[j].value_of(&Item::with_id(p as u32)).clone();
-
-                    if let Some(a) = &mut array[[row_position, j]] {
-                        a.push(val);
-                    } else {
-                        array[[row_position, j]] = Some(vec![val]);
-                    }
-                }
+            let values = decode_parts(var, var_parts);
+            if !values.is_empty() {
+                array[[row_position, j]] = Some(values);
             }
         }
-        row_position += 1;
     }
 
-    unsafe { array.assume_init() }
+    array
+}
+
+/// The values of the parts set in `var_parts`, the inverse of the encoding in [`encode_row`].
+pub(crate) fn decode_parts<T: Hash + Eq + Clone>(var: &Itemizer<T>, var_parts: &[bool]) -> Vec<T> {
+    var_parts
+        .iter()
+        .enumerate()
+        .filter(|(_, &set)| set)
+        .map(|(p, _)| var.value_of(&Item::with_id(p as u32)).clone())
+        .collect()
 }
 
 #[cfg(test)]
 mod test {
-    use itemizer::Itemizer;
-    use ndarray::{arr2, Axis};
+    use ndarray::{arr2, Array2};
 
-    use crate::multi_compress::espresso_compress;
+    use crate::multi_compress::{
+        canonicalize, column_itemizers, espresso_compress, espresso_compress_classes,
+        espresso_compress_forbidden, espresso_compress_with, try_espresso_compress,
+    };
+    use crate::EspressoOptions;
 
     #[test]
     fn test_espresso_compress() {
@@ -164,21 +437,9 @@ mod test {
             ],
         ]);
 
-        let mut variables = Vec::<Itemizer<String>>::new();
-
-        for column in matrix.axis_iter(Axis(1)) {
-            let mut itemizer = Itemizer::new();
-            for i in column {
-                if let Some(i) = i {
-                    for x in i {
-                        itemizer.id_of(x);
-                    }
-                }
-            }
-            variables.push(itemizer);
-        }
+        let variables = column_itemizers(&matrix);
 
-        let result = espresso_compress(&matrix, &variables);
+        let result = espresso_compress(&matrix, Some(&variables));
 
         assert_eq!(
             result,
@@ -196,4 +457,116 @@ mod test {
             ])
         );
     }
+
+    #[test]
+    fn test_espresso_compress_generic() {
+        let matrix = arr2(&[
+            [Some(vec![1u32]), Some(vec![10u32])],
+            [Some(vec![1]), Some(vec![20])],
+            [Some(vec![2]), Some(vec![30])],
+        ]);
+
+        let result = espresso_compress(&matrix, None);
+
+        assert_eq!(result.nrows(), 2);
+        assert!(result
+            .rows()
+            .into_iter()
+            .any(|row| row[0] == Some(vec![2]) && row[1] == Some(vec![30])));
+        let row = result
+            .rows()
+            .into_iter()
+            .find(|row| row[0] == Some(vec![1]))
+            .unwrap();
+        let mut values = row[1].clone().unwrap();
+        values.sort();
+        assert_eq!(values, vec![10, 20]);
+    }
+
+    #[test]
+    fn test_espresso_compress_classes() {
+        let matrix = arr2(&[
+            [Some(vec!["A"]), Some(vec!["X"])],
+            [Some(vec!["A"]), Some(vec!["Y"])],
+            [Some(vec!["B"]), Some(vec!["X"])],
+            [Some(vec!["B"]), Some(vec!["Y"])],
+            [Some(vec!["C"]), Some(vec!["X"])],
+        ]);
+        let classes = ["stop", "stop", "go", "wait", "go"];
+
+        let result = espresso_compress_classes(&matrix, &classes, None);
+
+        let classes: Vec<_> = result.iter().map(|(class, _)| *class).collect();
+        assert_eq!(classes, vec!["stop", "go", "wait"]);
+        assert_eq!(result[0].1, arr2(&[[Some(vec!["A"]), None]]));
+        assert_eq!(result[2].1, arr2(&[[Some(vec!["B"]), Some(vec!["Y"])]]));
+
+        let (_, go) = &result[1];
+        assert_eq!(go.nrows(), 1);
+        assert_eq!(go[[0, 1]], Some(vec!["X"]));
+        let mut values = go[[0, 0]].clone().unwrap();
+        values.sort();
+        assert_eq!(values, vec!["B", "C"]);
+    }
+
+    #[test]
+    fn test_espresso_compress_forbidden() {
+        let matrix = arr2(&[
+            [Some(vec!["A"]), Some(vec!["X"])],
+            [Some(vec!["B"]), Some(vec!["Y"])],
+        ]);
+        let forbidden = arr2(&[[Some(vec!["A"]), Some(vec!["Y"])]]);
+        let covers = |row: ndarray::ArrayView1<Option<Vec<&str>>>, a, b| {
+            row[0].iter().all(|v| v.contains(&a)) && row[1].iter().all(|v| v.contains(&b))
+        };
+
+        let result = espresso_compress_forbidden(&matrix, &forbidden, None);
+
+        for (a, b) in [("A", "X"), ("B", "Y")] {
+            assert!(result.rows().into_iter().any(|row| covers(row, a, b)));
+        }
+        assert!(!result.rows().into_iter().any(|row| covers(row, "A", "Y")));
+        // the gap (B, X) lets both rows drop a column
+        assert!(result
+            .rows()
+            .into_iter()
+            .all(|row| row[0].is_none() || row[1].is_none()));
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let first = arr2(&[
+            [Some(vec![1, 2]), Some(vec![10u32])],
+            [Some(vec![3]), None],
+            [Some(vec![4]), Some(vec![30])],
+        ]);
+        // the same rows in a different order, so the itemizers number the values differently
+        let second = arr2(&[
+            [Some(vec![4]), Some(vec![30])],
+            [Some(vec![2, 1]), Some(vec![10])],
+            [Some(vec![3]), None],
+        ]);
+
+        let options = EspressoOptions::default().canonical(true);
+        assert_eq!(
+            espresso_compress_with(&first, None, &options),
+            espresso_compress_with(&second, None, &options)
+        );
+
+        let first = espresso_compress(&first, None);
+        let second = espresso_compress(&second, None);
+
+        assert_eq!(canonicalize(&first), canonicalize(&second));
+    }
+
+    #[test]
+    fn test_espresso_compress_empty() {
+        let matrix: Array2<Option<Vec<u32>>> = Array2::from_shape_vec((0, 3), vec![]).unwrap();
+
+        assert_eq!(try_espresso_compress(&matrix, None).unwrap().dim(), (0, 3));
+        assert_eq!(
+            espresso_compress_classes::<_, u32>(&matrix, &[], None),
+            vec![]
+        );
+    }
 }
@@ -1,127 +1,113 @@
-use std::{
-    ffi::{c_char, CStr},
-    mem::MaybeUninit,
-    os::raw::c_void,
+use itemizer::Itemizer;
+use ndarray::{Array2, Axis};
+
+use crate::{
+    error::EspressoError,
+    minimize,
+    multi_compress::{canonicalize_with, decode_parts, encode_row},
+    EspressoOptions, OutputVal, PlaMultiValued, PlaType,
 };
 
-use itemizer::{Item, Itemizer};
-use ndarray::{Array2, AssignElem, Axis};
-use std::fmt::Write;
-
-use crate::{free, run_espresso_from_data};
-
-///
+/// Minimizes a matrix whose last column is the multi-valued output, `None` allows all values of
+/// a column.
 pub fn espresso_multi(
     matrix: &Array2<Option<Vec<String>>>,
-    variables: &Vec<Itemizer<String>>,
+    variables: &[Itemizer<String>],
 ) -> Array2<Option<Vec<String>>> {
-    let mut pla_string = String::new();
-
-    writeln!(
-        pla_string,
-        ".mv {} 0 {}",
-        matrix.dim().1,
-        variables
-            .iter()
-            .map(|x| x.len().to_string())
-            .collect::<Vec<String>>()
-            .join(" ")
-    )
-    .unwrap();
-
-    writeln!(pla_string, ".p {}", matrix.dim().0).unwrap();
-    writeln!(pla_string, ".type f").unwrap();
-
-    for row in matrix.axis_iter(Axis(0)) {
-        for (i, var) in row.iter().zip(variables) {
-            if let Some(i) = i {
-                let mut s = std::iter::repeat("0")
-                    .take(var.len() - 1)
-                    .collect::<String>();
-                for x in i {
-                    let id = var.id_of_opt(x).unwrap().as_index();
-
-                    s.insert(id, '1');
-                }
-                write!(pla_string, "{}", s).unwrap()
-            } else {
-                for _ in 0..var.len() {
-                    write!(pla_string, "1").unwrap()
-                }
-            }
-            write!(pla_string, "|").unwrap();
-        }
+    try_espresso_multi(matrix, variables).unwrap_or_else(|e| panic!("espresso failed: {}", e))
+}
 
-        writeln!(pla_string, "").unwrap();
-    }
+/// Like [`espresso_multi`], but reports a matrix not matching the itemizers and values missing
+/// from them as errors instead of panicking. A matrix without rows results in an empty matrix.
+pub fn try_espresso_multi(
+    matrix: &Array2<Option<Vec<String>>>,
+    variables: &[Itemizer<String>],
+) -> Result<Array2<Option<Vec<String>>>, EspressoError> {
+    try_espresso_multi_with(matrix, variables, &EspressoOptions::default())
+}
 
-    writeln!(pla_string, ".e").unwrap();
+/// Like [`espresso_multi`], but minimizes with the given options. With
+/// [`EspressoOptions::canonical`] the result is passed through
+/// [`canonicalize`](crate::canonicalize). The output type of the options is ignored, the result
+/// only holds the ON-set.
+pub fn espresso_multi_with(
+    matrix: &Array2<Option<Vec<String>>>,
+    variables: &[Itemizer<String>],
+    options: &EspressoOptions,
+) -> Array2<Option<Vec<String>>> {
+    try_espresso_multi_with(matrix, variables, options)
+        .unwrap_or_else(|e| panic!("espresso failed: {}", e))
+}
 
-    let bytes = pla_string.as_bytes();
-    let mut buf = MaybeUninit::<*mut c_char>::uninit();
-    unsafe {
-        run_espresso_from_data(
-            bytes.as_ptr() as *const c_char,
-            bytes.len() as u32,
-            buf.as_mut_ptr(),
-        );
+/// Like [`espresso_multi_with`], but reports errors like [`try_espresso_multi`].
+pub fn try_espresso_multi_with(
+    matrix: &Array2<Option<Vec<String>>>,
+    variables: &[Itemizer<String>],
+    options: &EspressoOptions,
+) -> Result<Array2<Option<Vec<String>>>, EspressoError> {
+    if variables.is_empty() || variables.len() != matrix.ncols() {
+        return Err(EspressoError::InvalidInput(format!(
+            "{} itemizers for {} columns, the last column is the output",
+            variables.len(),
+            matrix.ncols()
+        )));
     }
-    let c_str = Box::new(unsafe { buf.assume_init() });
-    let result = unsafe { CStr::from_ptr(*c_str).to_str().unwrap().to_owned() };
-    unsafe { free(*c_str as *mut c_void) };
 
-    let mut array = MaybeUninit::<Array2<Option<Vec<String>>>>::zeroed();
-    let mut row_position = 0;
+    let sizes = variables.iter().map(|x| x.len()).collect();
+    let mut pla = PlaMultiValued::new(0, sizes, PlaType::F);
 
-    for l in result.lines() {
-        if l.starts_with(".e") {
-            break;
-        }
-        if l.starts_with(".p") {
-            let len = l[3..l.len()].parse::<usize>().unwrap();
-
-            array.assign_elem(Array2::default((len, variables.len())));
-            continue;
-        }
-        if l.starts_with(".") {
-            continue;
-        }
-
-        let array = unsafe { array.assume_init_mut() };
+    for row in matrix.axis_iter(Axis(0)) {
+        let mut parts = encode_row(row, variables)?;
+
+        // the last column is the output variable
+        let outputs = parts
+            .split_off(parts.len() - variables[variables.len() - 1].len())
+            .into_iter()
+            .map(|p| if p { OutputVal::On } else { OutputVal::Off })
+            .collect();
+        pla.add_row(parts, outputs);
+    }
 
-        for (j, var) in l.split(' ').skip(1).enumerate() {
-            if j >= variables.len() {
-                break;
-            }
-            if matrix.dim().1 - 1 != j && var.chars().all(|x| x == '1') {
+    // espresso reports a PLA without rows as an empty cover, which compresses to nothing
+    let result = if pla.rows().next().is_none() {
+        pla
+    } else {
+        minimize(&pla, &options.clone().output_type(PlaType::F))?.0
+    };
+
+    let output = variables.len() - 1;
+    let mut array: Array2<Option<Vec<String>>> =
+        Array2::default((result.rows().count(), variables.len()));
+
+    for (row_position, (parts, outputs)) in result.rows().enumerate() {
+        for (j, var) in variables.iter().enumerate() {
+            let var_parts: Vec<bool> = if j == output {
+                outputs.iter().map(|&o| o == OutputVal::On).collect()
+            } else {
+                parts[result.var_parts(j)].to_vec()
+            };
+            if j != output && var_parts.iter().all(|&p| p) {
                 continue;
             }
-            for (p, c) in var.chars().enumerate() {
-                if c == '1' {
-                    let val = variables[j]
-                        .value_of(&Item::with_id((p + 1) as u32))
-                        .clone();
-
-                    if let Some(a) = &mut array[[row_position, j]] {
-                        a.push(val);
-                    } else {
-                        array[[row_position, j]] = Some(vec![val]);
-                    }
-                }
+            let values = decode_parts(var, &var_parts);
+            if !values.is_empty() {
+                array[[row_position, j]] = Some(values);
             }
         }
-        row_position += 1;
     }
 
-    unsafe { array.assume_init() }
+    Ok(canonicalize_with(array, options))
 }
 
 #[cfg(test)]
 mod test {
     use itemizer::Itemizer;
-    use ndarray::{arr2, Axis};
+    use ndarray::{arr2, Array2, Axis};
 
-    use crate::multi_reduce::espresso_multi;
+    use crate::{
+        multi_reduce::{espresso_multi, try_espresso_multi},
+        EspressoError,
+    };
 
     #[test]
     fn test_espresso_compress() {
@@ -147,12 +133,8 @@ mod test {
 
         for column in matrix.axis_iter(Axis(1)) {
             let mut itemizer = Itemizer::new();
-            for i in column {
-                if let Some(i) = i {
-                    for x in i {
-                        itemizer.id_of(x);
-                    }
-                }
+            for x in column.iter().flatten().flatten() {
+                itemizer.id_of(x);
             }
             variables.push(itemizer);
         }
@@ -175,4 +157,27 @@ mod test {
             ])
         );
     }
+
+    #[test]
+    fn test_try_espresso_multi() {
+        let mut variables = vec![Itemizer::new(), Itemizer::new()];
+        variables[0].id_of(&"A".to_string());
+        variables[1].id_of(&"U".to_string());
+
+        let empty = Array2::from_shape_vec((0, 2), vec![]).unwrap();
+        assert_eq!(
+            try_espresso_multi(&empty, &variables).unwrap().dim(),
+            (0, 2)
+        );
+
+        let unknown = arr2(&[[Some(vec!["B".to_string()]), None]]);
+        assert!(matches!(
+            try_espresso_multi(&unknown, &variables),
+            Err(EspressoError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            try_espresso_multi(&empty, &variables[..1]),
+            Err(EspressoError::InvalidInput(_))
+        ));
+    }
 }
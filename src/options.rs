@@ -0,0 +1,227 @@
+use std::os::raw::c_int;
+
+use crate::PlaType;
+
+/// The minimization algorithm run by [`espresso_with`](crate::espresso_with).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// The espresso heuristic.
+    #[default]
+    Espresso,
+    /// Provably minimum number of cubes (`-Dexact`), only feasible for small functions since all
+    /// primes are generated.
+    Exact,
+    /// Provably minimum number of literals, only correct for binary-valued inputs.
+    ExactLiterals,
+    /// Exact minimization based on essential signature cubes (`-Dsignature`), avoids generating
+    /// all primes and therefore scales to functions where [`Algorithm::Exact`] blows up.
+    Signature,
+    /// Fast heuristic based on the unate recursive paradigm (`-Dsimplify`), ignores the
+    /// don't-care set and usually gives a larger result than [`Algorithm::Espresso`].
+    Simplify,
+    /// Runs espresso on every output separately (`-Dso`), so no cubes are shared between
+    /// outputs.
+    SingleOutput,
+}
+
+impl Algorithm {
+    // Keep in sync with the ALGORITHM_* defines in api.c
+    fn to_raw(self) -> c_int {
+        match self {
+            Algorithm::Espresso => 0,
+            Algorithm::Exact => 1,
+            Algorithm::ExactLiterals => 2,
+            Algorithm::Signature => 3,
+            Algorithm::Simplify => 4,
+            Algorithm::SingleOutput => 5,
+        }
+    }
+}
+
+/// Options for a single espresso run, mirroring the `-e` flags of the espresso command line.
+///
+/// The defaults are the defaults of the espresso command line tool. The options only apply to
+/// the run they are passed to, espresso's global settings are restored afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EspressoOptions {
+    single_expand: bool,
+    use_super_gasp: bool,
+    remove_essential: bool,
+    force_irredundant: bool,
+    unwrap_onset: bool,
+    recompute_onset: bool,
+    pos: bool,
+    use_random_order: bool,
+    algorithm: Algorithm,
+    pub(crate) canonical: bool,
+    pub(crate) output_type: PlaType,
+}
+
+impl Default for EspressoOptions {
+    fn default() -> Self {
+        EspressoOptions {
+            single_expand: false,
+            use_super_gasp: false,
+            remove_essential: true,
+            force_irredundant: true,
+            unwrap_onset: true,
+            recompute_onset: false,
+            pos: false,
+            use_random_order: false,
+            algorithm: Algorithm::default(),
+            canonical: false,
+            output_type: PlaType::F,
+        }
+    }
+}
+
+impl EspressoOptions {
+    /// Stop after the first expand/irredundant step (`-efast`).
+    pub fn single_expand(mut self, value: bool) -> Self {
+        self.single_expand = value;
+        self
+    }
+
+    /// Use the slower super_gasp instead of last_gasp to escape local minima (`-estrong`).
+    pub fn use_super_gasp(mut self, value: bool) -> Self {
+        self.use_super_gasp = value;
+        self
+    }
+
+    /// Remove essential primes before iterating (disabled by `-eness`).
+    pub fn remove_essential(mut self, value: bool) -> Self {
+        self.remove_essential = value;
+        self
+    }
+
+    /// Iterate make_sparse until the result is irredundant (disabled by `-enirr`).
+    pub fn force_irredundant(mut self, value: bool) -> Self {
+        self.force_irredundant = value;
+        self
+    }
+
+    /// Unwrap the output part of the ON-set before the first expand (disabled by `-enunwrap`).
+    pub fn unwrap_onset(mut self, value: bool) -> Self {
+        self.unwrap_onset = value;
+        self
+    }
+
+    /// Recompute the ON-set with simplify before starting (`-eonset`).
+    pub fn recompute_onset(mut self, value: bool) -> Self {
+        self.recompute_onset = value;
+        self
+    }
+
+    /// Minimize the OFF-set instead of the ON-set, the result describes the complement of the
+    /// function (`-epos`).
+    pub fn pos(mut self, value: bool) -> Self {
+        self.pos = value;
+        self
+    }
+
+    /// Process the cubes in random order during expand and reduce (`-erandom`).
+    pub fn use_random_order(mut self, value: bool) -> Self {
+        self.use_random_order = value;
+        self
+    }
+
+    /// Selects the minimization algorithm, the flags above only affect [`Algorithm::Espresso`].
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sort the cubes of the result lexically (`-Dlexsort`), so the order of the result only
+    /// depends on the cubes and not on the order espresso found them in.
+    pub fn canonical(mut self, value: bool) -> Self {
+        self.canonical = value;
+        self
+    }
+
+    /// The sets of the result (`-o`): the minimized ON-set, followed by the don't-care set and
+    /// the OFF-set espresso used if the type has them. The default is the ON-set only.
+    pub fn output_type(mut self, output_type: PlaType) -> Self {
+        self.output_type = output_type;
+        self
+    }
+
+    pub(crate) fn to_raw(&self) -> RawOptions {
+        RawOptions {
+            single_expand: self.single_expand as c_int,
+            use_super_gasp: self.use_super_gasp as c_int,
+            remove_essential: self.remove_essential as c_int,
+            force_irredundant: self.force_irredundant as c_int,
+            unwrap_onset: self.unwrap_onset as c_int,
+            recompute_onset: self.recompute_onset as c_int,
+            pos: self.pos as c_int,
+            use_random_order: self.use_random_order as c_int,
+            algorithm: self.algorithm.to_raw(),
+            lex_sort: self.canonical as c_int,
+            out_type: self.output_type.to_raw(),
+        }
+    }
+}
+
+/// The options as passed to api.c, keep in sync with `espresso_options` there.
+#[repr(C)]
+pub(crate) struct RawOptions {
+    single_expand: c_int,
+    use_super_gasp: c_int,
+    remove_essential: c_int,
+    force_irredundant: c_int,
+    unwrap_onset: c_int,
+    recompute_onset: c_int,
+    pos: c_int,
+    use_random_order: c_int,
+    algorithm: c_int,
+    lex_sort: c_int,
+    out_type: c_int,
+}
+
+impl RawOptions {
+    /// The options as one line of integers, how they are handed to the workers of an
+    /// [`EspressoPool`](crate::EspressoPool).
+    pub(crate) fn to_line(&self) -> String {
+        let fields = [
+            self.single_expand,
+            self.use_super_gasp,
+            self.remove_essential,
+            self.force_irredundant,
+            self.unwrap_onset,
+            self.recompute_onset,
+            self.pos,
+            self.use_random_order,
+            self.algorithm,
+            self.lex_sort,
+            self.out_type,
+        ];
+        let fields: Vec<String> = fields.iter().map(c_int::to_string).collect();
+        fields.join(" ")
+    }
+
+    /// Reads the options written by [`to_line`](Self::to_line).
+    pub(crate) fn from_line(line: &str) -> Option<RawOptions> {
+        let fields = line
+            .split_whitespace()
+            .map(|f| f.parse().ok())
+            .collect::<Option<Vec<c_int>>>()?;
+        match fields[..] {
+            [single_expand, use_super_gasp, remove_essential, force_irredundant, unwrap_onset, recompute_onset, pos, use_random_order, algorithm, lex_sort, out_type] => {
+                Some(RawOptions {
+                    single_expand,
+                    use_super_gasp,
+                    remove_essential,
+                    force_irredundant,
+                    unwrap_onset,
+                    recompute_onset,
+                    pos,
+                    use_random_order,
+                    algorithm,
+                    lex_sort,
+                    out_type,
+                })
+            }
+            _ => None,
+        }
+    }
+}
@@ -1 +1,123 @@
-pub trait PLA: From<String> + ToString {}
+use std::fmt::Display;
+use std::os::raw::c_int;
+
+use rustsat::types::TernaryVal;
+
+use crate::{covers::Covers, error::EspressoError};
+
+pub trait PLA: From<String> + ToString {
+    /// Parses the textual PLA format, reporting malformed input instead of panicking.
+    fn parse(value: &str) -> Result<Self, EspressoError>;
+
+    /// Converts into espresso's cube representation, which lets the C core skip the PLA text
+    /// format. `None` if the type is only exchanged as text.
+    #[doc(hidden)]
+    fn to_covers(&self) -> Option<Result<Covers, EspressoError>> {
+        None
+    }
+
+    /// Builds the result of minimizing `self` from the resulting ON-set, keeping the labels of
+    /// `self`. `None` if the type cannot be built from covers, the result is then computed from
+    /// the PLA text instead.
+    #[doc(hidden)]
+    fn with_covers(&self, _covers: Covers) -> Option<Self> {
+        None
+    }
+}
+
+/// The logical type of a PLA (`.type`), selecting which sets its rows describe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaType {
+    /// Only the ON-set is given, the rest of the function is the OFF-set.
+    #[default]
+    F,
+    /// ON-set and don't-care set, the OFF-set is the complement of both
+    /// (espresso's default if a PLA has no `.type`).
+    Fd,
+    /// ON-set and OFF-set, the don't-care set is the complement of both.
+    Fr,
+    /// ON-set, don't-care set and OFF-set are all given explicitly.
+    Fdr,
+}
+
+impl PlaType {
+    /// The set bits of espresso's F_type, D_type and R_type.
+    pub(crate) fn to_raw(self) -> c_int {
+        match self {
+            PlaType::F => 1,
+            PlaType::Fd => 1 | 2,
+            PlaType::Fr => 1 | 4,
+            PlaType::Fdr => 1 | 2 | 4,
+        }
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<PlaType> {
+        match value {
+            "f" => Some(PlaType::F),
+            "fd" => Some(PlaType::Fd),
+            "fr" => Some(PlaType::Fr),
+            "fdr" => Some(PlaType::Fdr),
+            _ => None,
+        }
+    }
+}
+
+impl Display for PlaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlaType::F => write!(f, "f"),
+            PlaType::Fd => write!(f, "fd"),
+            PlaType::Fr => write!(f, "fr"),
+            PlaType::Fdr => write!(f, "fdr"),
+        }
+    }
+}
+
+/// The value of a single output in a row of a PLA.
+///
+/// Which set a row is added to depends on the [`PlaType`]: `On` rows belong to the ON-set,
+/// `DontCare` rows to the don't-care set if the type has one and `Off` rows to the OFF-set if the
+/// type has one. In all other cases the row says nothing about the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputVal {
+    /// `1`
+    On,
+    /// `0`
+    Off,
+    /// `-`
+    DontCare,
+    /// `~`, the row says nothing about this output.
+    Unspecified,
+}
+
+impl OutputVal {
+    pub(crate) fn from_char(c: char) -> Option<OutputVal> {
+        // espresso itself also writes 4, 3 and 2 for 1, 0 and -
+        match c {
+            '1' | '4' => Some(OutputVal::On),
+            '0' | '3' => Some(OutputVal::Off),
+            '-' | '2' => Some(OutputVal::DontCare),
+            '~' => Some(OutputVal::Unspecified),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_char(self) -> char {
+        match self {
+            OutputVal::On => '1',
+            OutputVal::Off => '0',
+            OutputVal::DontCare => '-',
+            OutputVal::Unspecified => '~',
+        }
+    }
+}
+
+impl From<TernaryVal> for OutputVal {
+    fn from(value: TernaryVal) -> Self {
+        match value {
+            TernaryVal::True => OutputVal::On,
+            TernaryVal::False => OutputVal::Off,
+            TernaryVal::DontCare => OutputVal::DontCare,
+        }
+    }
+}
@@ -1,35 +1,422 @@
-use std::{fmt::Debug, vec};
+use std::{
+    fmt::{self, Debug, Display},
+    vec,
+};
 
 use rustsat::{
     instances::Cnf,
     types::{Clause, Lit, TernaryVal},
 };
 
-use crate::pla::PLA;
+use crate::{
+    combine, complement,
+    covers::Covers,
+    equivalent_outputs,
+    error::EspressoError,
+    implies, is_tautology,
+    pla::{OutputVal, PlaType, PLA},
+    primes, CoverOp, EquivalentOutputs, EspressoOptions,
+};
 
 struct Lines {
     inputs: Vec<TernaryVal>,
-    outputs: Vec<TernaryVal>,
+    outputs: Vec<OutputVal>,
+}
+pub struct PlaBinary {
+    pla_type: PlaType,
+    input_labels: Option<Vec<String>>,
+    output_labels: Option<Vec<String>>,
+    // the .i and .o of a parsed PLA, keeps the size of PLAs without rows
+    declared: (Option<usize>, Option<usize>),
+    lines: Vec<Lines>,
 }
-pub struct PlaBinary(Vec<Lines>);
 
 impl Default for PlaBinary {
     fn default() -> Self {
-        PlaBinary(Vec::new())
+        PlaBinary::with_type(PlaType::default())
     }
 }
 
 impl PlaBinary {
+    /// Creates an empty PLA of the given type.
+    pub fn with_type(pla_type: PlaType) -> Self {
+        PlaBinary {
+            pla_type,
+            input_labels: None,
+            output_labels: None,
+            declared: (None, None),
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn pla_type(&self) -> PlaType {
+        self.pla_type
+    }
+
+    /// Adds a row, the outputs are mapped to `1`, `0` and `-` respectively.
     pub fn add_line(&mut self, inputs: Vec<TernaryVal>, outputs: Vec<TernaryVal>) {
-        self.0.push(Lines { inputs, outputs });
+        let outputs = outputs.into_iter().map(OutputVal::from).collect();
+        self.add_row(inputs, outputs);
+    }
+
+    /// Adds a row with explicit output values, see [`OutputVal`] for their meaning.
+    pub fn add_row(&mut self, inputs: Vec<TernaryVal>, outputs: Vec<OutputVal>) {
+        self.lines.push(Lines { inputs, outputs });
+    }
+
+    /// Names the inputs (`.ilb`), one label per input. A wrong number of labels, or an empty
+    /// label or one containing whitespace, is reported as `InvalidInput`.
+    pub fn set_input_labels(&mut self, labels: Vec<String>) -> Result<(), EspressoError> {
+        let inputs = self
+            .lines
+            .first()
+            .map(|l| l.inputs.len())
+            .or(self.declared.0);
+        check_labels(&labels, inputs, "inputs")?;
+        self.input_labels = Some(labels);
+        Ok(())
+    }
+
+    /// Names the outputs (`.ob`), one label per output, checked like
+    /// [`set_input_labels`](Self::set_input_labels).
+    pub fn set_output_labels(&mut self, labels: Vec<String>) -> Result<(), EspressoError> {
+        let outputs = self
+            .lines
+            .first()
+            .map(|l| l.outputs.len())
+            .or(self.declared.1);
+        check_labels(&labels, outputs, "outputs")?;
+        self.output_labels = Some(labels);
+        Ok(())
+    }
+
+    pub fn input_labels(&self) -> Option<&[String]> {
+        self.input_labels.as_deref()
+    }
+
+    pub fn output_labels(&self) -> Option<&[String]> {
+        self.output_labels.as_deref()
+    }
+
+    /// The column of the input with the given label.
+    pub fn input_index(&self, label: &str) -> Option<usize> {
+        self.input_labels.as_ref()?.iter().position(|l| l == label)
+    }
+
+    /// The column of the output with the given label.
+    pub fn output_index(&self, label: &str) -> Option<usize> {
+        self.output_labels.as_ref()?.iter().position(|l| l == label)
+    }
+
+    /// Iterates over the inputs and outputs of all rows.
+    pub fn rows(&self) -> impl Iterator<Item = (&[TernaryVal], &[OutputVal])> {
+        self.lines
+            .iter()
+            .map(|l| (l.inputs.as_slice(), l.outputs.as_slice()))
+    }
+
+    /// The OFF-set of the PLA as an ON-set (`.type f`), the minterms that are neither in the
+    /// ON-set nor in the don't-care set. PLAs of type `fr` and `fdr` give their OFF-set
+    /// explicitly.
+    ///
+    /// The OFF-set is computed by espresso's complementation and not minimized, unless
+    /// `minimize` gives the options to minimize it with, as [`crate::try_espresso_with`] would
+    /// with the ON-set as OFF-set. A minimized OFF-set that fails verification is reported as
+    /// [`EspressoError::VerificationFailed`].
+    pub fn complement(
+        &self,
+        minimize: Option<&EspressoOptions>,
+    ) -> Result<PlaBinary, EspressoError> {
+        complement(self, minimize)
+    }
+
+    /// All prime implicants of the ON-set and don't-care set (espresso `-Dprimes`), as an
+    /// ON-set (`.type f`). Primes only covering don't-cares are included, a prime covers as many
+    /// outputs as possible.
+    ///
+    /// The number of primes can grow exponentially with the number of inputs. `max_returned` only
+    /// caps the number of primes returned: all of them are still generated, so it bounds neither
+    /// time nor memory, and which primes are kept is arbitrary.
+    pub fn primes(&self, max_returned: Option<usize>) -> Result<PlaBinary, EspressoError> {
+        primes(self, max_returned)
+    }
+
+    /// Whether the PLA is on for every input and output, counting the don't-care set as on
+    /// (espresso `-Dtaut` only looks at the ON-set).
+    pub fn is_tautology(&self) -> Result<bool, EspressoError> {
+        is_tautology(self)
+    }
+
+    /// The pairs of outputs that describe the same function, possibly after negating either of
+    /// them (espresso `-Dequiv`). An output counts as on wherever it is not off, so its
+    /// don't-care set is on and off in its negation.
+    pub fn equivalent_outputs(&self) -> Result<Vec<EquivalentOutputs>, EspressoError> {
+        equivalent_outputs(self)
+    }
+
+    /// Whether no row turns an output on.
+    pub fn is_empty(&self) -> bool {
+        !self
+            .rows()
+            .any(|(_, outputs)| outputs.contains(&OutputVal::On))
+    }
+
+    /// Whether every minterm of the cube is in the ON-set or the don't-care set. The cube spans
+    /// the outputs that are [`OutputVal::On`] in `outputs`.
+    pub fn covers_cube(
+        &self,
+        inputs: &[TernaryVal],
+        outputs: &[OutputVal],
+    ) -> Result<bool, EspressoError> {
+        let mut cube = PlaBinary::with_type(PlaType::F);
+        cube.add_row(
+            inputs.to_vec(),
+            outputs
+                .iter()
+                .map(|&o| match o {
+                    OutputVal::On => OutputVal::On,
+                    _ => OutputVal::Off,
+                })
+                .collect(),
+        );
+
+        cube.implies(self)
+    }
+
+    /// Whether the ON-set of `self` lies within the ON-set and don't-care set of `other`, that
+    /// is every input turning an output of `self` on may turn it on in `other` as well.
+    /// Both PLAs must have the same number of inputs and outputs.
+    pub fn implies(&self, other: &PlaBinary) -> Result<bool, EspressoError> {
+        implies(self, other)
+    }
+
+    /// The union of both ON-sets (espresso `-Dunion`), without cubes contained in others.
+    ///
+    /// Like the other operations on two covers, this requires both PLAs to have the same number
+    /// of inputs and outputs and returns an ON-set (`.type f`) with the labels of `self`. The
+    /// result is not minimized.
+    pub fn union(&self, other: &PlaBinary) -> Result<PlaBinary, EspressoError> {
+        combine(self, other, CoverOp::Union)
+    }
+
+    /// The intersection of both ON-sets (espresso `-Dintersect`).
+    pub fn intersect(&self, other: &PlaBinary) -> Result<PlaBinary, EspressoError> {
+        combine(self, other, CoverOp::Intersect)
+    }
+
+    /// The ON-set of `self` without the one of `other` (espresso `-Dsharp`).
+    pub fn sharp(&self, other: &PlaBinary) -> Result<PlaBinary, EspressoError> {
+        combine(self, other, CoverOp::Sharp)
+    }
+
+    /// Like [`PlaBinary::sharp`], but the resulting cubes are pairwise disjoint
+    /// (espresso `-Ddsharp`).
+    pub fn disjoint_sharp(&self, other: &PlaBinary) -> Result<PlaBinary, EspressoError> {
+        combine(self, other, CoverOp::DisjointSharp)
+    }
+
+    /// The minterms in the ON-set of one PLA and the OFF-set of the other (espresso `-Dxor`).
+    /// Unlike the other operations this respects the don't-care sets, which are excluded from
+    /// the OFF-sets.
+    pub fn xor(&self, other: &PlaBinary) -> Result<PlaBinary, EspressoError> {
+        combine(self, other, CoverOp::Xor)
     }
 }
 
-impl PLA for PlaBinary {}
+impl PLA for PlaBinary {
+    fn parse(value: &str) -> Result<Self, EspressoError> {
+        // espresso reads a PLA without .type as fd
+        let mut result = PlaBinary::with_type(PlaType::Fd);
+
+        for (n, l) in value.lines().enumerate() {
+            if l.starts_with(".e") {
+                break;
+            }
+            let malformed = |message: &str| EspressoError::MalformedPla {
+                line: n + 1,
+                message: message.to_owned(),
+            };
+
+            if let Some(pla_type) = l.strip_prefix(".type") {
+                result.pla_type = PlaType::parse(pla_type.trim())
+                    .ok_or_else(|| malformed("unsupported .type, expected f, fd, fr or fdr"))?;
+                continue;
+            }
+            if let Some(size) = l.strip_prefix(".i ").or_else(|| l.strip_prefix(".o ")) {
+                let size = size
+                    .trim()
+                    .parse()
+                    .map_err(|_| malformed("invalid .i or .o"))?;
+                if l.starts_with(".i") {
+                    result.declared.0 = Some(size);
+                } else {
+                    result.declared.1 = Some(size);
+                }
+                continue;
+            }
+            if let Some(labels) = l.strip_prefix(".ilb ") {
+                result.input_labels = Some(labels.split_whitespace().map(String::from).collect());
+                continue;
+            }
+            if let Some(labels) = l.strip_prefix(".ob ") {
+                result.output_labels = Some(labels.split_whitespace().map(String::from).collect());
+                continue;
+            }
+            if l.starts_with('.') || l.starts_with('#') || l.trim().is_empty() {
+                continue;
+            }
+
+            let parts: Vec<&str> = l.split_whitespace().collect();
+            if parts.len() != 2 {
+                return Err(malformed(
+                    "expected inputs and outputs separated by a space",
+                ));
+            }
+            let inputs = parse_values(parts[0]).ok_or_else(|| malformed("invalid input value"))?;
+            let outputs = parts[1]
+                .chars()
+                .map(OutputVal::from_char)
+                .collect::<Option<_>>()
+                .ok_or_else(|| malformed("invalid output value"))?;
+
+            result.add_row(inputs, outputs);
+        }
+
+        Ok(result)
+    }
+
+    fn to_covers(&self) -> Option<Result<Covers, EspressoError>> {
+        Some(self.try_to_covers())
+    }
+
+    fn with_covers(&self, covers: Covers) -> Option<Self> {
+        let mut result = PlaBinary::with_type(covers.pla_type());
+        result.input_labels = self.input_labels.clone();
+        result.output_labels = self.output_labels.clone();
+        // the ON-set may be empty, keep the size for printing
+        result.declared = (Some(covers.num_binary_vars()), Some(covers.num_outputs()));
+
+        let inputs = covers.num_binary_vars();
+        for cube in covers.on_set() {
+            let (input_parts, output_parts) = cube.split_at(2 * inputs);
+            let inputs = input_parts
+                .chunks(2)
+                .map(|parts| match parts {
+                    [0, _] => TernaryVal::True,
+                    [_, 0] => TernaryVal::False,
+                    _ => TernaryVal::DontCare,
+                })
+                .collect();
+            let outputs = output_parts
+                .iter()
+                .map(|&part| {
+                    if part != 0 {
+                        OutputVal::On
+                    } else {
+                        OutputVal::Off
+                    }
+                })
+                .collect();
+            result.add_row(inputs, outputs);
+        }
+
+        Some(result)
+    }
+}
+
+/// `.ilb` and `.ob` are split at whitespace and must hold exactly one label per column.
+fn check_labels(labels: &[String], width: Option<usize>, kind: &str) -> Result<(), EspressoError> {
+    if let Some(width) = width.filter(|&w| w != labels.len()) {
+        return Err(EspressoError::InvalidInput(format!(
+            "{} labels for {} {}",
+            labels.len(),
+            width,
+            kind
+        )));
+    }
+    if let Some(label) = labels
+        .iter()
+        .find(|l| l.is_empty() || l.contains(char::is_whitespace))
+    {
+        return Err(EspressoError::InvalidInput(format!(
+            "label {:?} is empty or contains whitespace",
+            label
+        )));
+    }
+    Ok(())
+}
+
+impl PlaBinary {
+    /// The number of inputs and outputs, taken from the first row, the labels or the declared
+    /// size.
+    fn width(&self) -> (usize, usize) {
+        let width = |first: Option<usize>, labels: &Option<Vec<String>>, declared| {
+            first
+                .or_else(|| labels.as_ref().map(Vec::len))
+                .or(declared)
+                .unwrap_or(0)
+        };
+        (
+            width(
+                self.lines.first().map(|l| l.inputs.len()),
+                &self.input_labels,
+                self.declared.0,
+            ),
+            width(
+                self.lines.first().map(|l| l.outputs.len()),
+                &self.output_labels,
+                self.declared.1,
+            ),
+        )
+    }
+
+    fn try_to_covers(&self) -> Result<Covers, EspressoError> {
+        let (inputs, outputs) = self.width();
+        let mut covers = Covers::new(inputs, &[outputs], self.pla_type);
+
+        let mut parts = Vec::with_capacity(2 * inputs);
+        for (n, line) in self.lines.iter().enumerate() {
+            if line.inputs.len() != inputs || line.outputs.len() != outputs {
+                return Err(EspressoError::InvalidInput(format!(
+                    "row {} has {} inputs and {} outputs instead of {} and {}",
+                    n,
+                    line.inputs.len(),
+                    line.outputs.len(),
+                    inputs,
+                    outputs
+                )));
+            }
+
+            parts.clear();
+            for value in &line.inputs {
+                parts.extend_from_slice(match value {
+                    TernaryVal::True => &[0, 1],
+                    TernaryVal::False => &[1, 0],
+                    TernaryVal::DontCare => &[1, 1],
+                });
+            }
+            covers.push_row(&parts, &line.outputs);
+        }
+
+        Ok(covers)
+    }
+}
+
+fn parse_values(values: &str) -> Option<Vec<TernaryVal>> {
+    values
+        .chars()
+        .map(|c| match c {
+            '1' => Some(TernaryVal::True),
+            '0' => Some(TernaryVal::False),
+            '-' => Some(TernaryVal::DontCare),
+            _ => None,
+        })
+        .collect()
+}
 
 impl Debug for PlaBinary {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for line in &self.0 {
+        for line in &self.lines {
             for i in &line.inputs {
                 write!(f, "{:?}", i)?;
             }
@@ -37,7 +424,7 @@ impl Debug for PlaBinary {
             for i in &line.outputs {
                 write!(f, "{:?}", i)?;
             }
-            writeln!(f, "")?;
+            writeln!(f)?;
         }
         Ok(())
     }
@@ -45,42 +432,7 @@ impl Debug for PlaBinary {
 
 impl From<String> for PlaBinary {
     fn from(value: String) -> Self {
-        let mut result = Vec::new();
-
-        let lines: Vec<&str> = value.lines().collect();
-
-        for l in lines {
-            if l.starts_with(".e") {
-                break;
-            }
-            if l.starts_with(".") {
-                continue;
-            }
-            let parts: Vec<&str> = l.split(' ').collect();
-            let inputs_str = parts[0];
-            let outputs_str = parts[1];
-            let mut inputs: Vec<TernaryVal> = Vec::new();
-            let mut outputs: Vec<TernaryVal> = Vec::new();
-            for c in inputs_str.chars() {
-                match c {
-                    '1' => inputs.push(TernaryVal::True),
-                    '0' => inputs.push(TernaryVal::False),
-                    '-' => inputs.push(TernaryVal::DontCare),
-                    _ => panic!("Invalid character in PLA file"),
-                }
-            }
-            for c in outputs_str.chars() {
-                match c {
-                    '1' => outputs.push(TernaryVal::True),
-                    '0' => outputs.push(TernaryVal::False),
-                    '-' => outputs.push(TernaryVal::DontCare),
-                    _ => panic!("Invalid character in PLA file"),
-                }
-            }
-            result.push(Lines { inputs, outputs });
-        }
-
-        PlaBinary(result)
+        PlaBinary::parse(&value).unwrap_or_else(|e| panic!("Invalid PLA: {}", e))
     }
 }
 
@@ -88,12 +440,25 @@ impl From<&PlaBinary> for String {
     fn from(pla: &PlaBinary) -> Self {
         //implement function to convert a tuple of two vectors into anappropriate PLA string representation
         let mut result = String::new();
+        let (inputs, outputs) = pla.width();
         result.push_str(".i ");
-        result.push_str(&(pla.0[0].inputs.len().to_string()));
+        result.push_str(&inputs.to_string());
         result.push_str("\n.o ");
-        result.push_str(&(pla.0[0].outputs.len().to_string()));
-        result.push_str("\n.type f\n");
-        for f in &pla.0 {
+        result.push_str(&outputs.to_string());
+        result.push_str("\n.type ");
+        result.push_str(&pla.pla_type.to_string());
+        result.push('\n');
+        if let Some(labels) = &pla.input_labels {
+            result.push_str(".ilb ");
+            result.push_str(&labels.join(" "));
+            result.push('\n');
+        }
+        if let Some(labels) = &pla.output_labels {
+            result.push_str(".ob ");
+            result.push_str(&labels.join(" "));
+            result.push('\n');
+        }
+        for f in &pla.lines {
             for i in 0..f.inputs.len() {
                 match f.inputs[i] {
                     TernaryVal::True => result.push('1'),
@@ -102,12 +467,8 @@ impl From<&PlaBinary> for String {
                 }
             }
             result.push(' ');
-            for i in 0..f.outputs.len() {
-                match f.outputs[i] {
-                    TernaryVal::True => result.push('1'),
-                    TernaryVal::False => result.push('0'),
-                    TernaryVal::DontCare => result.push('-'),
-                }
+            for o in &f.outputs {
+                result.push(o.to_char());
             }
             result.push('\n');
         }
@@ -116,36 +477,58 @@ impl From<&PlaBinary> for String {
     }
 }
 
-impl ToString for PlaBinary {
-    fn to_string(&self) -> String {
-        String::from(self)
+impl Display for PlaBinary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from(self))
     }
 }
 
 impl PlaBinary {
     pub fn from_cnf(cnf: Cnf, max_id: u32) -> Self {
+        PlaBinary::try_from_cnf(cnf, max_id).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_from_cnf(cnf: Cnf, max_id: u32) -> Result<Self, EspressoError> {
         let mut pla = PlaBinary::default();
 
         for clause in cnf {
             let mut inputs = vec![TernaryVal::DontCare; max_id as usize];
+            let mut tautology = false;
 
             for i in clause {
                 // Inverted because the CNF is optimized invertedly to form a DNF
                 // Therefor the literal is inverted according to DeMorgan's Law
 
-                inputs[i.vidx()] = TernaryVal::from(i.is_neg());
+                if i.vidx() >= inputs.len() {
+                    return Err(EspressoError::InvalidInput(format!(
+                        "variable {} exceeds max_id {}",
+                        i.vidx(),
+                        max_id
+                    )));
+                }
+                let value = TernaryVal::from(i.is_neg());
+                tautology |= ![TernaryVal::DontCare, value].contains(&inputs[i.vidx()]);
+                inputs[i.vidx()] = value;
             }
 
-            pla.add_line(inputs, vec![TernaryVal::True]);
+            // a clause with both x and !x is always satisfied, its negation has no cube
+            if !tautology {
+                pla.add_line(inputs, vec![TernaryVal::True]);
+            }
         }
 
-        pla
+        Ok(pla)
     }
 
     pub fn to_cnf(&self) -> Cnf {
         let mut cnf = Cnf::new();
 
-        for line in &self.0 {
+        // only the ON-set describes the DNF
+        for line in self
+            .lines
+            .iter()
+            .filter(|l| l.outputs.contains(&OutputVal::On))
+        {
             let mut clause = Clause::new();
 
             for (i, val) in line.inputs.iter().enumerate() {
@@ -167,7 +550,18 @@ impl PlaBinary {
 mod test {
     use rustsat::{clause, instances::Cnf, lit};
 
-    use crate::espresso_cnf;
+    use rustsat::types::TernaryVal;
+
+    use crate::{
+        espresso, espresso_cnf, pla::PLA, try_espresso_cnf, verify, EquivalentOutputs,
+        EspressoError, EspressoOptions, OutputVal, PlaType,
+    };
+
+    use super::PlaBinary;
+
+    fn pla(inputs: usize, outputs: usize, rows: &str) -> PlaBinary {
+        PlaBinary::parse(&format!(".i {}\n.o {}\n{}.e\n", inputs, outputs, rows)).unwrap()
+    }
 
     #[test]
     fn cnf() {
@@ -177,8 +571,289 @@ mod test {
         cnf.add_clause(clause!(lit![1], lit![4]));
         cnf.add_clause(clause!(lit![1], lit![5]));
 
-        let opt = espresso_cnf(cnf, 6);
+        let opt = espresso_cnf(cnf);
 
         println!("{:?}", opt);
     }
+
+    #[test]
+    fn cnf_out_of_range() {
+        let mut cnf = Cnf::new();
+        cnf.add_clause(clause!(lit![1], lit![7]));
+
+        assert!(matches!(
+            PlaBinary::try_from_cnf(cnf, 6),
+            Err(EspressoError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn cnf_tautological_clause() {
+        // (x1 | !x1 | x2) is always satisfied, only (x3) is left
+        let mut cnf = Cnf::new();
+        cnf.add_clause(clause!(lit![1], !lit![1], lit![2]));
+        cnf.add_clause(clause!(lit![3]));
+
+        assert_eq!(PlaBinary::from_cnf(cnf.clone(), 4).rows().count(), 1);
+
+        let mut expected = Cnf::new();
+        expected.add_clause(clause!(lit![3]));
+        assert_eq!(try_espresso_cnf(cnf).unwrap(), expected);
+    }
+
+    #[test]
+    fn cnf_empty() {
+        assert_eq!(espresso_cnf(Cnf::new()), Cnf::new());
+        assert_eq!(try_espresso_cnf(Cnf::new()).unwrap(), Cnf::new());
+
+        let mut cnf = Cnf::new();
+        cnf.add_clause(clause!(lit![1], !lit![1]));
+        assert_eq!(espresso_cnf(cnf), Cnf::new());
+    }
+
+    #[test]
+    fn cnf_sparse() {
+        // (x3 | x1000000) & (x3 | !x1000000) is x3
+        let mut cnf = Cnf::new();
+        cnf.add_clause(clause!(lit![3], lit![1_000_000]));
+        cnf.add_clause(clause!(lit![3], !lit![1_000_000]));
+
+        let opt = try_espresso_cnf(cnf).unwrap();
+
+        let mut expected = Cnf::new();
+        expected.add_clause(clause!(lit![3]));
+        assert_eq!(opt, expected);
+    }
+
+    #[test]
+    fn parse_malformed() {
+        let result = PlaBinary::parse(".i 2\n.o 1\n01 1\n0x 1\n.e\n");
+
+        assert!(matches!(
+            result,
+            Err(EspressoError::MalformedPla { line: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn pla_type() {
+        let pla = PlaBinary::parse(".i 2\n.o 2\n.type fdr\n11 1~\n10 -0\n.e\n").unwrap();
+        assert_eq!(pla.pla_type(), PlaType::Fdr);
+        assert_eq!(
+            pla.rows().map(|(_, o)| o.to_vec()).collect::<Vec<_>>(),
+            vec![
+                vec![OutputVal::On, OutputVal::Unspecified],
+                vec![OutputVal::DontCare, OutputVal::Off]
+            ]
+        );
+        assert_eq!(pla.to_string(), ".i 2\n.o 2\n.type fdr\n11 1~\n10 -0\n.e\n");
+
+        // espresso reads a PLA without .type as fd
+        let pla = PlaBinary::parse(".i 1\n.o 1\n1 1\n.e\n").unwrap();
+        assert_eq!(pla.pla_type(), PlaType::Fd);
+    }
+
+    #[test]
+    fn dont_care_and_off_set() {
+        let t = TernaryVal::True;
+        let f = TernaryVal::False;
+
+        // 10 is a don't-care, so x0 alone covers the ON-set
+        let mut pla = PlaBinary::with_type(PlaType::Fd);
+        pla.add_row(vec![t, t], vec![OutputVal::On]);
+        pla.add_row(vec![t, f], vec![OutputVal::DontCare]);
+        let result = espresso(pla);
+        assert_eq!(result.pla_type(), PlaType::F);
+        assert_eq!(result.to_string(), ".i 2\n.o 1\n.type f\n1- 1\n.e\n");
+
+        // only 00 is in the OFF-set, everything else not in the ON-set is a don't-care
+        let mut pla = PlaBinary::with_type(PlaType::Fr);
+        pla.add_row(vec![t, t], vec![OutputVal::On]);
+        pla.add_row(vec![f, t], vec![OutputVal::On]);
+        pla.add_row(vec![f, f], vec![OutputVal::Off]);
+        let result = espresso(pla);
+        assert_eq!(result.to_string(), ".i 2\n.o 1\n.type f\n-1 1\n.e\n");
+    }
+
+    #[test]
+    fn labels() {
+        let mut pla = PlaBinary::default();
+        pla.set_input_labels(vec!["a".to_owned(), "b".to_owned()])
+            .unwrap();
+        pla.set_output_labels(vec!["f".to_owned()]).unwrap();
+        pla.add_line(
+            vec![TernaryVal::True, TernaryVal::True],
+            vec![TernaryVal::True],
+        );
+        pla.add_line(
+            vec![TernaryVal::True, TernaryVal::False],
+            vec![TernaryVal::True],
+        );
+        assert_eq!(
+            pla.to_string(),
+            ".i 2\n.o 1\n.type f\n.ilb a b\n.ob f\n11 1\n10 1\n.e\n"
+        );
+
+        // the labels survive the minimization
+        let result = espresso(pla);
+        assert_eq!(
+            result.to_string(),
+            ".i 2\n.o 1\n.type f\n.ilb a b\n.ob f\n1- 1\n.e\n"
+        );
+        assert_eq!(result.input_index("b"), Some(1));
+        assert_eq!(result.output_index("f"), Some(0));
+        assert_eq!(result.input_index("c"), None);
+    }
+
+    #[test]
+    fn labels_invalid() {
+        let mut pla = pla(3, 1, "1-0 1\n");
+        let labels = |labels: &[&str]| labels.iter().map(|&l| l.to_owned()).collect();
+
+        assert!(matches!(
+            pla.set_input_labels(labels(&["a", "b"])),
+            Err(EspressoError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            pla.set_input_labels(labels(&["a x", "b", "c"])),
+            Err(EspressoError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            pla.set_output_labels(labels(&["f", "g"])),
+            Err(EspressoError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            pla.set_output_labels(labels(&[""])),
+            Err(EspressoError::InvalidInput(_))
+        ));
+        assert_eq!(pla.input_labels(), None);
+        assert_eq!(pla.output_labels(), None);
+
+        // the declared size counts for a PLA without rows
+        let mut empty = PlaBinary::parse(".i 2\n.o 1\n.e\n").unwrap();
+        assert!(empty.set_input_labels(labels(&["a"])).is_err());
+        empty.set_input_labels(labels(&["a", "b"])).unwrap();
+        assert_eq!(empty.to_string(), ".i 2\n.o 1\n.type fd\n.ilb a b\n.e\n");
+    }
+
+    #[test]
+    fn cover_operations() {
+        let a = pla(2, 1, "1- 1\n");
+        let b = pla(2, 1, "-1 1\n");
+        let assert_same = |result: &PlaBinary, expected: &str| {
+            assert_eq!(result.pla_type(), PlaType::F);
+            assert_eq!(verify(&pla(2, 1, expected), result), Ok(()));
+        };
+
+        assert_same(&a.union(&b).unwrap(), "1- 1\n01 1\n");
+        assert_same(&a.intersect(&b).unwrap(), "11 1\n");
+        assert_same(&a.sharp(&b).unwrap(), "10 1\n");
+        assert_same(&a.xor(&b).unwrap(), "10 1\n01 1\n");
+
+        let dsharp = pla(2, 1, "-- 1\n")
+            .disjoint_sharp(&pla(2, 1, "00 1\n"))
+            .unwrap();
+        assert_same(&dsharp, "1- 1\n01 1\n");
+        assert_eq!(dsharp.rows().count(), 2);
+
+        // an empty result keeps its size
+        let empty = pla(2, 1, "10 1\n").intersect(&b).unwrap();
+        assert_eq!(empty.rows().count(), 0);
+        assert_eq!(empty.to_string(), ".i 2\n.o 1\n.type f\n.e\n");
+        assert_same(&empty.union(&b).unwrap(), "-1 1\n");
+
+        assert!(matches!(
+            a.union(&pla(3, 1, "1-- 1\n")),
+            Err(EspressoError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn complement() {
+        let off_set = pla(2, 1, "11 1\n00 -\n").complement(None).unwrap();
+        assert_eq!(off_set.pla_type(), PlaType::F);
+        assert_eq!(verify(&pla(2, 1, "10 1\n01 1\n"), &off_set), Ok(()));
+
+        let minimized = pla(2, 1, "11 1\n10 1\n")
+            .complement(Some(&EspressoOptions::default()))
+            .unwrap();
+        assert_eq!(minimized.to_string(), ".i 2\n.o 1\n.type f\n0- 1\n.e\n");
+
+        // the OFF-set of an empty cover is everything, the one of a tautology is empty
+        assert_eq!(
+            pla(2, 1, "").complement(None).unwrap().to_string(),
+            ".i 2\n.o 1\n.type f\n-- 1\n.e\n"
+        );
+        assert_eq!(
+            pla(2, 1, "-- 1\n").complement(None).unwrap().rows().count(),
+            0
+        );
+    }
+
+    #[test]
+    fn queries() {
+        let f = pla(2, 2, "1- 10\n01 11\n00 -0\n");
+
+        assert!(!f.is_tautology().unwrap());
+        assert!(pla(2, 2, "1- 11\n0- 11\n").is_tautology().unwrap());
+        // the don't-care set counts as covered
+        assert!(pla(2, 2, "1- 11\n0- 1-\n").is_tautology().unwrap());
+
+        assert!(!f.is_empty());
+        assert!(pla(2, 2, "1- 00\n0- -0\n").is_empty());
+
+        let cube = |inputs: &str, outputs: &str| {
+            let row = pla(2, 2, &format!("{} {}\n", inputs, outputs));
+            let (inputs, outputs) = row.rows().next().unwrap();
+            f.covers_cube(inputs, outputs).unwrap()
+        };
+        assert!(cube("-0", "10"));
+        assert!(cube("11", "10"));
+        assert!(!cube("-1", "11"));
+        assert!(cube("--", "10"));
+        assert!(!cube("--", "01"));
+
+        assert!(pla(2, 2, "11 10\n").implies(&f).unwrap());
+        assert!(!f.implies(&pla(2, 2, "11 10\n")).unwrap());
+        assert!(matches!(
+            f.implies(&pla(3, 2, "111 10\n")),
+            Err(EspressoError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn equivalent_outputs() {
+        // x0 & x1, x0 & x1, !(x0 & x1) and x0
+        let pla = pla(2, 4, "11 1101\n10 0011\n0- 0010\n");
+
+        let equivalent = |first, second, first_negated, second_negated| EquivalentOutputs {
+            first,
+            second,
+            first_negated,
+            second_negated,
+        };
+        assert_eq!(
+            pla.equivalent_outputs().unwrap(),
+            vec![
+                equivalent(0, 1, false, false),
+                equivalent(0, 2, false, true),
+                equivalent(1, 2, false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn primes() {
+        // the primes of x0 | x1 & x2 with don't-care !x0 & !x1 & !x2
+        let pla = pla(3, 1, "1-- 1\n-11 1\n000 -\n");
+
+        let primes = pla.primes(None).unwrap();
+        let text = primes.to_string();
+        let mut rows: Vec<_> = text.lines().filter(|l| !l.starts_with('.')).collect();
+        rows.sort_unstable();
+        assert_eq!(rows, ["-00 1", "-11 1", "1-- 1"]);
+
+        assert_eq!(pla.primes(Some(2)).unwrap().rows().count(), 2);
+        assert_eq!(pla.primes(Some(0)).unwrap().rows().count(), 0);
+    }
 }
@@ -0,0 +1,544 @@
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+use crate::{
+    complement,
+    covers::Covers,
+    equivalent_outputs,
+    error::EspressoError,
+    implies, is_tautology,
+    pla::{OutputVal, PlaType, PLA},
+    EquivalentOutputs, EspressoOptions,
+};
+
+#[derive(Debug, Clone)]
+struct Row {
+    parts: Vec<bool>,
+    outputs: Vec<OutputVal>,
+}
+
+/// A PLA with multi-valued variables (`.mv`).
+///
+/// The binary variables come first, followed by the multi-valued ones. The last multi-valued
+/// variable is the output. Every row stores one flag per part of the input variables: two parts
+/// per binary variable (value 0 and value 1) and one per value of a multi-valued variable. A row
+/// allows all values whose parts are set.
+#[derive(Debug, Clone)]
+pub struct PlaMultiValued {
+    num_binary_vars: usize,
+    sizes: Vec<usize>,
+    pla_type: PlaType,
+    input_labels: Option<Vec<String>>,
+    labels: Vec<Option<Vec<String>>>,
+    rows: Vec<Row>,
+}
+
+impl PlaMultiValued {
+    /// Creates an empty PLA with `num_binary_vars` binary variables followed by multi-valued
+    /// variables of the given sizes, the last of which is the output.
+    pub fn new(num_binary_vars: usize, sizes: Vec<usize>, pla_type: PlaType) -> Self {
+        assert!(!sizes.is_empty(), "the output variable is missing");
+
+        PlaMultiValued {
+            num_binary_vars,
+            labels: vec![None; sizes.len()],
+            sizes,
+            pla_type,
+            input_labels: None,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn num_binary_vars(&self) -> usize {
+        self.num_binary_vars
+    }
+
+    /// The number of variables, including the binary ones and the output.
+    pub fn num_vars(&self) -> usize {
+        self.num_binary_vars + self.sizes.len()
+    }
+
+    /// The sizes of the multi-valued variables, the last one is the number of outputs.
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    pub fn pla_type(&self) -> PlaType {
+        self.pla_type
+    }
+
+    /// The number of parts of the input variables in a row.
+    fn num_input_parts(&self) -> usize {
+        2 * self.num_binary_vars + self.sizes[..self.sizes.len() - 1].iter().sum::<usize>()
+    }
+
+    fn num_outputs(&self) -> usize {
+        self.sizes[self.sizes.len() - 1]
+    }
+
+    /// The parts of the input variable `var` within the parts of a row.
+    pub fn var_parts(&self, var: usize) -> Range<usize> {
+        assert!(
+            var + 1 < self.num_vars(),
+            "{} is not an input variable",
+            var
+        );
+
+        if var < self.num_binary_vars {
+            return 2 * var..2 * var + 2;
+        }
+        let mv = var - self.num_binary_vars;
+        let first = 2 * self.num_binary_vars + self.sizes[..mv].iter().sum::<usize>();
+        first..first + self.sizes[mv]
+    }
+
+    /// Adds a row, `parts` holds the parts of all input variables
+    /// (see [`PlaMultiValued::var_parts`]).
+    pub fn add_row(&mut self, parts: Vec<bool>, outputs: Vec<OutputVal>) {
+        self.rows.push(Row { parts, outputs });
+    }
+
+    /// Iterates over the input parts and outputs of all rows.
+    pub fn rows(&self) -> impl Iterator<Item = (&[bool], &[OutputVal])> {
+        self.rows
+            .iter()
+            .map(|r| (r.parts.as_slice(), r.outputs.as_slice()))
+    }
+
+    /// Names the binary variables (`.ilb`), one label per variable.
+    pub fn set_input_labels(&mut self, labels: Vec<String>) {
+        self.input_labels = Some(labels);
+    }
+
+    pub fn input_labels(&self) -> Option<&[String]> {
+        self.input_labels.as_deref()
+    }
+
+    /// Names the values of the multi-valued variable `var` (`.label`, or `.ob` for the output),
+    /// one label per value. Labels must not contain whitespace.
+    pub fn set_labels(&mut self, var: usize, labels: Vec<String>) {
+        let mv = var
+            .checked_sub(self.num_binary_vars)
+            .unwrap_or_else(|| panic!("{} is not a multi-valued variable", var));
+        self.labels[mv] = Some(labels);
+    }
+
+    pub fn labels(&self, var: usize) -> Option<&[String]> {
+        self.labels[var.checked_sub(self.num_binary_vars)?].as_deref()
+    }
+
+    /// The value of the multi-valued variable `var` with the given label.
+    pub fn value_index(&self, var: usize, label: &str) -> Option<usize> {
+        self.labels(var)?.iter().position(|l| l == label)
+    }
+
+    /// Whether the PLA is on for every input and output, see [`crate::PlaBinary::is_tautology`].
+    pub fn is_tautology(&self) -> Result<bool, EspressoError> {
+        is_tautology(self)
+    }
+
+    /// The pairs of values of the output variable that describe the same function, see
+    /// [`crate::PlaBinary::equivalent_outputs`].
+    pub fn equivalent_outputs(&self) -> Result<Vec<EquivalentOutputs>, EspressoError> {
+        equivalent_outputs(self)
+    }
+
+    /// Whether no row turns an output on for any input.
+    pub fn is_empty(&self) -> bool {
+        let input_vars = 0..self.num_vars() - 1;
+        !self.rows().any(|(parts, outputs)| {
+            outputs.contains(&OutputVal::On)
+                && input_vars
+                    .clone()
+                    .all(|var| parts[self.var_parts(var)].contains(&true))
+        })
+    }
+
+    /// Whether every minterm of the cube is in the ON-set or the don't-care set, see
+    /// [`crate::PlaBinary::covers_cube`].
+    pub fn covers_cube(
+        &self,
+        parts: &[bool],
+        outputs: &[OutputVal],
+    ) -> Result<bool, EspressoError> {
+        let mut cube = PlaMultiValued::new(self.num_binary_vars, self.sizes.clone(), PlaType::F);
+        cube.add_row(
+            parts.to_vec(),
+            outputs
+                .iter()
+                .map(|&o| match o {
+                    OutputVal::On => OutputVal::On,
+                    _ => OutputVal::Off,
+                })
+                .collect(),
+        );
+
+        cube.implies(self)
+    }
+
+    /// Whether the ON-set of `self` lies within the ON-set and don't-care set of `other`, see
+    /// [`crate::PlaBinary::implies`].
+    pub fn implies(&self, other: &PlaMultiValued) -> Result<bool, EspressoError> {
+        implies(self, other)
+    }
+
+    /// The OFF-set of the PLA as an ON-set, see [`crate::PlaBinary::complement`].
+    pub fn complement(
+        &self,
+        minimize: Option<&EspressoOptions>,
+    ) -> Result<PlaMultiValued, EspressoError> {
+        complement(self, minimize)
+    }
+}
+
+impl PLA for PlaMultiValued {
+    fn parse(value: &str) -> Result<Self, EspressoError> {
+        let mut result: Option<PlaMultiValued> = None;
+        let mut inputs = None;
+        // espresso reads a PLA without .type as fd
+        let mut pla_type = PlaType::Fd;
+
+        for (n, l) in value.lines().enumerate() {
+            if l.starts_with(".e") {
+                break;
+            }
+            let malformed = |message: &str| EspressoError::MalformedPla {
+                line: n + 1,
+                message: message.to_owned(),
+            };
+            let numbers = |words: &[&str]| {
+                words
+                    .iter()
+                    .map(|w| w.parse::<usize>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| malformed("expected a number"))
+            };
+            let number = |word: Option<&&str>| {
+                word.and_then(|w| w.parse::<usize>().ok())
+                    .ok_or_else(|| malformed("expected a number"))
+            };
+
+            let words: Vec<&str> = l.split_whitespace().collect();
+            match words.first().copied() {
+                Some(".mv") => {
+                    let numbers = numbers(&words[1..])?;
+                    if numbers.len() < 2 || numbers[0] <= numbers[1] {
+                        return Err(malformed("expected .mv num_vars num_binary_vars sizes"));
+                    }
+                    if numbers.len() != 2 + numbers[0] - numbers[1] {
+                        return Err(malformed("expected one size per multi-valued variable"));
+                    }
+                    result = Some(PlaMultiValued::new(
+                        numbers[1],
+                        numbers[2..].to_vec(),
+                        pla_type,
+                    ));
+                }
+                Some(".i") => inputs = Some(number(words.get(1))?),
+                Some(".o") => {
+                    let inputs = inputs.ok_or_else(|| malformed(".o cannot appear before .i"))?;
+                    let outputs = number(words.get(1))?;
+                    result = Some(PlaMultiValued::new(inputs, vec![outputs], pla_type));
+                }
+                Some(".type") => {
+                    pla_type = words
+                        .get(1)
+                        .and_then(|t| PlaType::parse(t))
+                        .ok_or_else(|| malformed("unsupported .type, expected f, fd, fr or fdr"))?;
+                    if let Some(result) = &mut result {
+                        result.pla_type = pla_type;
+                    }
+                }
+                Some(".ilb") => {
+                    let result = result
+                        .as_mut()
+                        .ok_or_else(|| malformed("PLA size must be declared before .ilb"))?;
+                    result.input_labels = Some(words[1..].iter().map(|&w| w.to_owned()).collect());
+                }
+                Some(".ob") => {
+                    let result = result
+                        .as_mut()
+                        .ok_or_else(|| malformed("PLA size must be declared before .ob"))?;
+                    let output = result.num_vars() - 1;
+                    result.set_labels(output, words[1..].iter().map(|&w| w.to_owned()).collect());
+                }
+                Some(".label") => {
+                    let result = result
+                        .as_mut()
+                        .ok_or_else(|| malformed("PLA size must be declared before .label"))?;
+                    let var = words
+                        .get(1)
+                        .and_then(|w| w.strip_prefix("var="))
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .filter(|&v| v >= result.num_binary_vars && v < result.num_vars())
+                        .ok_or_else(|| malformed("expected var= and a multi-valued variable"))?;
+                    result.set_labels(var, words[2..].iter().map(|&w| w.to_owned()).collect());
+                }
+                Some(w) if w.starts_with('.') || w.starts_with('#') => {}
+                None => {}
+                Some(_) => {
+                    let result = result
+                        .as_mut()
+                        .ok_or_else(|| malformed("PLA size must be declared before the rows"))?;
+                    let row = parse_row(l, result).map_err(|m| malformed(&m))?;
+                    result.rows.push(row);
+                }
+            }
+        }
+
+        result.ok_or_else(|| {
+            EspressoError::Parse("no PLA size (.i/.o or .mv) found in input".to_owned())
+        })
+    }
+
+    fn to_covers(&self) -> Option<Result<Covers, EspressoError>> {
+        let mut covers = Covers::new(self.num_binary_vars, &self.sizes, self.pla_type);
+
+        let mut parts = Vec::with_capacity(self.num_input_parts());
+        for (n, row) in self.rows.iter().enumerate() {
+            if row.parts.len() != self.num_input_parts() || row.outputs.len() != self.num_outputs()
+            {
+                return Some(Err(EspressoError::InvalidInput(format!(
+                    "row {} has {} parts and {} outputs instead of {} and {}",
+                    n,
+                    row.parts.len(),
+                    row.outputs.len(),
+                    self.num_input_parts(),
+                    self.num_outputs()
+                ))));
+            }
+
+            parts.clear();
+            parts.extend(row.parts.iter().map(|&p| p as u8));
+            covers.push_row(&parts, &row.outputs);
+        }
+
+        Some(Ok(covers))
+    }
+
+    fn with_covers(&self, covers: Covers) -> Option<Self> {
+        let mut result = self.clone();
+        result.pla_type = covers.pla_type();
+        result.rows.clear();
+
+        let input_parts = self.num_input_parts();
+        for cube in covers.on_set() {
+            let (parts, outputs) = cube.split_at(input_parts);
+            result.add_row(
+                parts.iter().map(|&p| p != 0).collect(),
+                outputs
+                    .iter()
+                    .map(|&p| {
+                        if p != 0 {
+                            OutputVal::On
+                        } else {
+                            OutputVal::Off
+                        }
+                    })
+                    .collect(),
+            );
+        }
+
+        Some(result)
+    }
+}
+
+/// Parses a row the way espresso does, spaces and `|` between the values are ignored.
+fn parse_row(line: &str, pla: &PlaMultiValued) -> Result<Row, String> {
+    let values: Vec<char> = line
+        .chars()
+        .filter(|&c| !c.is_whitespace() && c != '|')
+        .collect();
+    let num_mv_parts = pla.num_input_parts() - 2 * pla.num_binary_vars;
+    let expected = pla.num_binary_vars + num_mv_parts + pla.num_outputs();
+    if values.len() != expected {
+        return Err(format!(
+            "expected {} values, found {}",
+            expected,
+            values.len()
+        ));
+    }
+
+    let (binary, rest) = values.split_at(pla.num_binary_vars);
+    let (mv, outputs) = rest.split_at(num_mv_parts);
+
+    let mut parts = Vec::with_capacity(pla.num_input_parts());
+    for &c in binary {
+        parts.extend_from_slice(match c {
+            '0' => &[true, false],
+            '1' => &[false, true],
+            '-' | '2' => &[true, true],
+            '?' => &[false, false],
+            _ => return Err(format!("invalid binary value {:?}", c)),
+        });
+    }
+    for &c in mv {
+        parts.push(match c {
+            '1' => true,
+            '0' => false,
+            _ => return Err(format!("invalid multi-valued part {:?}", c)),
+        });
+    }
+    let outputs = outputs
+        .iter()
+        .map(|&c| OutputVal::from_char(c).ok_or_else(|| format!("invalid output value {:?}", c)))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Row { parts, outputs })
+}
+
+impl From<String> for PlaMultiValued {
+    fn from(value: String) -> Self {
+        PlaMultiValued::parse(&value).unwrap_or_else(|e| panic!("Invalid PLA: {}", e))
+    }
+}
+
+impl From<&PlaMultiValued> for String {
+    fn from(pla: &PlaMultiValued) -> Self {
+        let mut result = format!(".mv {} {}", pla.num_vars(), pla.num_binary_vars);
+        for size in &pla.sizes {
+            result.push_str(&format!(" {}", size));
+        }
+        result.push_str(&format!("\n.type {}\n", pla.pla_type));
+
+        if let Some(labels) = &pla.input_labels {
+            result.push_str(&format!(".ilb {}\n", labels.join(" ")));
+        }
+        let output = pla.sizes.len() - 1;
+        for (mv, labels) in pla.labels.iter().enumerate() {
+            match labels {
+                Some(labels) if mv == output => {
+                    result.push_str(&format!(".ob {}\n", labels.join(" ")))
+                }
+                Some(labels) => result.push_str(&format!(
+                    ".label var={} {}\n",
+                    pla.num_binary_vars + mv,
+                    labels.join(" ")
+                )),
+                None => {}
+            }
+        }
+
+        for row in &pla.rows {
+            let (binary, mv) = row.parts.split_at(2 * pla.num_binary_vars);
+            for parts in binary.chunks(2) {
+                result.push(match parts {
+                    [true, false] => '0',
+                    [false, true] => '1',
+                    [true, true] => '-',
+                    _ => '?',
+                });
+            }
+            let mut mv = mv;
+            for &size in &pla.sizes[..output] {
+                let (var, rest) = mv.split_at(size);
+                result.push(' ');
+                result.extend(var.iter().map(|&p| if p { '1' } else { '0' }));
+                mv = rest;
+            }
+            result.push(' ');
+            result.extend(row.outputs.iter().map(|o| o.to_char()));
+            result.push('\n');
+        }
+        result.push_str(".e\n");
+        result
+    }
+}
+
+impl Display for PlaMultiValued {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from(self))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{espresso, pla::PLA, verify, EspressoOptions, OutputVal, PlaType};
+
+    use super::PlaMultiValued;
+
+    #[test]
+    fn round_trip() {
+        let text = ".mv 3 1 3 2\n.type fd\n.ilb a\n.label var=1 x y z\n.ob f g\n\
+                    1 100 1-\n- 011 ~1\n.e\n";
+        let pla = PlaMultiValued::parse(text).unwrap();
+
+        assert_eq!(pla.num_vars(), 3);
+        assert_eq!(pla.sizes(), &[3, 2]);
+        assert_eq!(pla.var_parts(1), 2..5);
+        assert_eq!(pla.value_index(1, "y"), Some(1));
+        assert_eq!(pla.value_index(2, "g"), Some(1));
+        assert_eq!(
+            pla.rows().next().unwrap(),
+            (
+                &[false, true, true, false, false][..],
+                &[OutputVal::On, OutputVal::DontCare][..]
+            )
+        );
+        assert_eq!(pla.to_string(), text);
+
+        // binary PLAs are read as a single output variable
+        let pla = PlaMultiValued::parse(".i 2\n.o 1\n.type f\n1- 1\n.e\n").unwrap();
+        assert_eq!(pla.to_string(), ".mv 3 2 1\n.type f\n1- 1\n.e\n");
+    }
+
+    #[test]
+    fn minimize() {
+        let mut pla = PlaMultiValued::new(0, vec![3, 1], PlaType::F);
+        pla.set_labels(0, vec!["x".to_owned(), "y".to_owned(), "z".to_owned()]);
+        pla.add_row(vec![true, false, false], vec![OutputVal::On]);
+        pla.add_row(vec![false, true, false], vec![OutputVal::On]);
+
+        let result = espresso(pla);
+
+        assert_eq!(
+            result.to_string(),
+            ".mv 2 0 3 1\n.type f\n.label var=0 x y z\n 110 1\n.e\n"
+        );
+    }
+
+    #[test]
+    fn complement() {
+        let mut pla = PlaMultiValued::new(1, vec![3, 1], PlaType::F);
+        pla.add_row(vec![true, true, true, false, false], vec![OutputVal::On]);
+        pla.add_row(vec![false, true, false, true, false], vec![OutputVal::On]);
+        let mut expected = PlaMultiValued::new(1, vec![3, 1], PlaType::F);
+        expected.add_row(vec![true, false, false, true, true], vec![OutputVal::On]);
+        expected.add_row(vec![true, true, false, false, true], vec![OutputVal::On]);
+
+        let off_set = pla.complement(None).unwrap();
+        assert_eq!(verify(&expected, &off_set), Ok(()));
+
+        let minimized = pla.complement(Some(&EspressoOptions::default())).unwrap();
+        assert_eq!(verify(&expected, &minimized), Ok(()));
+        assert_eq!(minimized.rows().count(), 2);
+    }
+
+    #[test]
+    fn queries() {
+        let mut pla = PlaMultiValued::new(0, vec![3, 1], PlaType::F);
+        pla.add_row(vec![true, true, false], vec![OutputVal::On]);
+
+        assert!(!pla.is_empty());
+        assert!(!pla.is_tautology().unwrap());
+        assert!(pla
+            .covers_cube(&[false, true, false], &[OutputVal::On])
+            .unwrap());
+        assert!(!pla
+            .covers_cube(&[false, true, true], &[OutputVal::On])
+            .unwrap());
+
+        let mut rest = PlaMultiValued::new(0, vec![3, 1], PlaType::F);
+        rest.add_row(vec![false, false, true], vec![OutputVal::On]);
+        assert!(!rest.implies(&pla).unwrap());
+        pla.add_row(vec![false, false, true], vec![OutputVal::On]);
+        assert!(rest.implies(&pla).unwrap());
+        assert!(pla.is_tautology().unwrap());
+
+        // a row without any value of a variable is empty
+        let mut empty = PlaMultiValued::new(0, vec![3, 1], PlaType::F);
+        empty.add_row(vec![false, false, false], vec![OutputVal::On]);
+        assert!(empty.is_empty());
+        assert!(empty.implies(&rest).unwrap());
+    }
+}
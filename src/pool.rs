@@ -0,0 +1,161 @@
+use std::io::{self, Read, Write};
+use std::os::raw::c_int;
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::{
+    minimize_result, options::RawOptions, run_espresso_from_data, run_from_data, EspressoError,
+    EspressoOptions, PLA,
+};
+
+/// Minimizes many PLAs in parallel, each in its own worker process.
+///
+/// A worker is a fresh process running [`serve_worker`], by default the `espresso-rs` command
+/// line tool found in `PATH` (see [`with_worker`](Self::with_worker)). Since every run happens
+/// in a separate process, runs do not share espresso's global state and are not serialized by
+/// the crate-wide lock. A run that crashes inside the C core only takes its worker down and is
+/// reported as [`EspressoError::WorkerFailed`].
+#[derive(Debug, Clone)]
+pub struct EspressoPool {
+    workers: usize,
+    options: EspressoOptions,
+    worker: PathBuf,
+}
+
+impl EspressoPool {
+    /// The argument a worker executable is started with, it then has to call [`serve_worker`].
+    pub const WORKER_ARG: &'static str = "--worker";
+
+    /// Creates a pool running at most `workers` processes at once (at least one).
+    pub fn new(workers: usize) -> Self {
+        EspressoPool {
+            workers: workers.max(1),
+            options: EspressoOptions::default(),
+            worker: PathBuf::from("espresso-rs"),
+        }
+    }
+
+    /// Uses the given options for every run instead of the defaults.
+    pub fn with_options(mut self, options: EspressoOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Runs the workers with the given executable instead of `espresso-rs`. It is started with
+    /// [`WORKER_ARG`](Self::WORKER_ARG) as its only argument and has to call [`serve_worker`]
+    /// then, e.g. the host program itself (`std::env::current_exe`) checking for it first thing
+    /// in `main`.
+    pub fn with_worker(mut self, worker: impl Into<PathBuf>) -> Self {
+        self.worker = worker.into();
+        self
+    }
+
+    /// Minimizes every PLA like [`try_espresso_with`](crate::try_espresso_with) would, the
+    /// results are returned in the order of the input.
+    pub fn espresso<P: PLA>(&self, plas: &[P]) -> Vec<Result<P, EspressoError>> {
+        let inputs: Vec<String> = plas.iter().map(|pla| pla.to_string()).collect();
+        let options = self.options.to_raw().to_line();
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![None; inputs.len()]);
+
+        thread::scope(|s| {
+            for _ in 0..self.workers.min(inputs.len()) {
+                s.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= inputs.len() {
+                        break;
+                    }
+                    let result = self.minimize_in_worker(&inputs[i], &options);
+                    results.lock().unwrap()[i] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| {
+                let (result, verified) = result.expect("every PLA is minimized")?;
+                if !verified {
+                    return Err(EspressoError::VerificationFailed);
+                }
+                P::parse(&result)
+            })
+            .collect()
+    }
+
+    /// Runs the minimization in a worker process, see [`serve_worker`] for what is exchanged.
+    fn minimize_in_worker(
+        &self,
+        pla_string: &str,
+        options: &str,
+    ) -> Result<(String, bool), EspressoError> {
+        let mut child = Command::new(&self.worker)
+            .arg(Self::WORKER_ARG)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                EspressoError::WorkerFailed(format!("could not start the worker process: {}", e))
+            })?;
+
+        // the worker reads all of its input before writing, so this cannot block on its output;
+        // if it died early, its exit status below tells why
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let _ = writeln!(stdin, "{}", options).and_then(|_| stdin.write_all(pla_string.as_bytes()));
+        drop(stdin);
+
+        let output = child.wait_with_output().map_err(|_| EspressoError::Io)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (status, result) = stdout
+            .split_once('\n')
+            .filter(|_| output.status.success())
+            .and_then(|(status, result)| Some((status.parse::<c_int>().ok()?, result)))
+            .ok_or_else(|| EspressoError::WorkerFailed(describe(output.status)))?;
+
+        minimize_result(status, Some(result.to_owned()).filter(|r| !r.is_empty()))
+    }
+}
+
+/// How a worker that did not report a result terminated.
+fn describe(status: ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return format!("terminated by signal {}", signal);
+        }
+    }
+    match status.code() {
+        Some(0) | None => "terminated without a result".to_owned(),
+        Some(code) => format!("exited with status {}", code),
+    }
+}
+
+/// Serves one run of an [`EspressoPool`] in a worker process: reads a line of options followed
+/// by the PLA text from standard input, minimizes it and writes the status followed by the output
+/// to standard output.
+///
+/// The worker is a process of its own, started with [`EspressoPool::WORKER_ARG`], so espresso's
+/// global state and a crash in the C core stay confined to it.
+pub fn serve_worker() -> io::Result<()> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    let (options, pla_string) = input
+        .split_once('\n')
+        .and_then(|(options, pla_string)| Some((RawOptions::from_line(options)?, pla_string)))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed worker input"))?;
+
+    let (status, result) = run_from_data(pla_string, |data, l, out| unsafe {
+        run_espresso_from_data(data, l, &options, out)
+    });
+
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", status)?;
+    stdout.write_all(result.unwrap_or_default().as_bytes())?;
+    stdout.flush()
+}
@@ -0,0 +1,10 @@
+/*
+  Compiles reduce.c with a way to reset its static state.
+  reduce() alternates between two cube orders on every call, a library run has to start with the
+  same order as a fresh espresso process or its result depends on the runs before.
+ */
+#include "reduce.c"
+
+void espresso_rs_reset_reduce(void) {
+  toggle = TRUE;
+}
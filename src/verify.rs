@@ -0,0 +1,185 @@
+use std::fmt::{self, Display};
+use std::os::raw::{c_char, c_int, c_uint};
+
+use crate::{error::STATUS_NOT_EQUIVALENT, run_from_pair, EspressoError, PLA};
+
+extern "C" {
+    fn run_verify_from_data(
+        original: *const c_char,
+        original_length: c_uint,
+        minimized: *const c_char,
+        minimized_length: c_uint,
+        out: *mut *mut c_char,
+    ) -> c_int;
+}
+
+/// An input on which two covers differ, see [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample {
+    /// The value of every input variable in the order of the espresso cube: 0 or 1 for the
+    /// binary variables, the index of the value for multi-valued ones.
+    pub inputs: Vec<usize>,
+    /// The output the covers differ in.
+    pub output: usize,
+    /// Whether the output is on in the original cover and off in the minimized one, otherwise
+    /// it is the other way around.
+    pub on_in_original: bool,
+}
+
+impl Counterexample {
+    fn parse(text: &str) -> Option<Counterexample> {
+        let mut values = text
+            .split_whitespace()
+            .map(|v| v.parse::<usize>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        if values.len() < 2 {
+            return None;
+        }
+        let on_in_original = values.remove(0) == 1;
+        let output = values.pop()?;
+
+        Some(Counterexample {
+            inputs: values,
+            output,
+            on_in_original,
+        })
+    }
+}
+
+impl Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inputs: Vec<_> = self.inputs.iter().map(usize::to_string).collect();
+        let (original, minimized) = if self.on_in_original { (1, 0) } else { (0, 1) };
+        write!(
+            f,
+            "output {} is {} in the original and {} in the minimized cover for inputs {}",
+            self.output,
+            original,
+            minimized,
+            inputs.join(" ")
+        )
+    }
+}
+
+/// Checks that `minimized` is equivalent to `original` (espresso `-Dverify`).
+///
+/// Every minterm of either ON-set has to be covered by the other ON-set together with the
+/// don't-care set of `original`, so `minimized` may be any cover espresso could have returned
+/// for `original`. If both PLAs name all of their inputs and outputs, the columns of `minimized`
+/// are matched to those of `original` by name first (`-DPLAverify`), so reordered columns still
+/// compare equal. Returns an input the covers differ on otherwise, in the column order of
+/// `original`.
+///
+/// Panics if the PLAs cannot be read or do not have the same variables, see [`try_verify`].
+pub fn verify<P: PLA>(original: &P, minimized: &P) -> Result<(), Counterexample> {
+    match try_verify(original, minimized) {
+        Ok(None) => Ok(()),
+        Ok(Some(counterexample)) => Err(counterexample),
+        Err(e) => panic!("verify failed: {}", e),
+    }
+}
+
+/// Like [`verify`], but returns an error instead of panicking. Equivalent covers give `None`.
+pub fn try_verify<P: PLA>(
+    original: &P,
+    minimized: &P,
+) -> Result<Option<Counterexample>, EspressoError> {
+    let (status, output) = run_from_pair(
+        &original.to_string(),
+        &minimized.to_string(),
+        |original, original_length, minimized, minimized_length, out| unsafe {
+            run_verify_from_data(original, original_length, minimized, minimized_length, out)
+        },
+    );
+
+    if status == STATUS_NOT_EQUIVALENT {
+        let output = output.ok_or(EspressoError::Io)?;
+        return Counterexample::parse(&output)
+            .map(Some)
+            .ok_or_else(|| EspressoError::Parse(format!("bad counterexample \"{}\"", output)));
+    }
+    EspressoError::from_output(status, output)?;
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use rustsat::types::TernaryVal;
+
+    use crate::{try_verify, verify, EspressoError, OutputVal, PlaBinary, PlaType};
+
+    fn pla(rows: &[(&str, &str)]) -> PlaBinary {
+        let mut pla = PlaBinary::with_type(PlaType::Fd);
+        for (inputs, outputs) in rows {
+            pla.add_row(
+                inputs
+                    .chars()
+                    .map(|c| match c {
+                        '0' => TernaryVal::False,
+                        '1' => TernaryVal::True,
+                        _ => TernaryVal::DontCare,
+                    })
+                    .collect(),
+                outputs
+                    .chars()
+                    .map(|c| OutputVal::from_char(c).unwrap())
+                    .collect(),
+            );
+        }
+        pla
+    }
+
+    #[test]
+    fn test_verify() {
+        let original = pla(&[("00", "1"), ("01", "1"), ("11", "-")]);
+
+        assert_eq!(verify(&original, &pla(&[("0-", "1")])), Ok(()));
+        // the don't-care minterm 11 may be covered or not
+        assert_eq!(verify(&original, &pla(&[("-1", "1"), ("00", "1")])), Ok(()));
+
+        let counterexample = verify(&original, &pla(&[("00", "1")])).unwrap_err();
+        assert_eq!(counterexample.inputs, vec![0, 1]);
+        assert_eq!(counterexample.output, 0);
+        assert!(counterexample.on_in_original);
+
+        let counterexample = verify(&original, &pla(&[("--", "1")])).unwrap_err();
+        assert_eq!(counterexample.inputs, vec![1, 0]);
+        assert!(!counterexample.on_in_original);
+    }
+
+    #[test]
+    fn test_verify_permuted() {
+        let mut original = pla(&[("10", "10"), ("01", "01")]);
+        original
+            .set_input_labels(vec!["a".to_owned(), "b".to_owned()])
+            .unwrap();
+        original
+            .set_output_labels(vec!["f".to_owned(), "g".to_owned()])
+            .unwrap();
+        let mut minimized = pla(&[("01", "10"), ("10", "01")]);
+        minimized
+            .set_input_labels(vec!["b".to_owned(), "a".to_owned()])
+            .unwrap();
+        minimized
+            .set_output_labels(vec!["f".to_owned(), "g".to_owned()])
+            .unwrap();
+
+        assert_eq!(verify(&original, &minimized), Ok(()));
+
+        // without names the columns are compared as they are
+        let unnamed = pla(&[("01", "10"), ("10", "01")]);
+        assert!(verify(&pla(&[("10", "10"), ("01", "01")]), &unnamed).is_err());
+    }
+
+    #[test]
+    fn test_try_verify_errors() {
+        let original = pla(&[("00", "1")]);
+
+        assert!(matches!(
+            try_verify(&original, &pla(&[("000", "1")])),
+            Err(EspressoError::InvalidInput(_))
+        ));
+        assert_eq!(try_verify(&original, &original), Ok(None));
+    }
+}
@@ -0,0 +1,45 @@
+use espresso_rs::{espresso, EspressoError, EspressoPool, PlaBinary};
+use rustsat::types::TernaryVal;
+
+/// A pool running the `espresso-rs` binary of this package as its workers.
+fn pool(workers: usize) -> EspressoPool {
+    EspressoPool::new(workers).with_worker(env!("CARGO_BIN_EXE_espresso-rs"))
+}
+
+#[test]
+fn test_pool_in_order() {
+    let plas: Vec<_> = (0..10)
+        .map(|i| {
+            let mut pla = PlaBinary::default();
+            pla.add_line(
+                vec![TernaryVal::from(i % 2 == 0), TernaryVal::True],
+                vec![TernaryVal::True],
+            );
+            pla.add_line(
+                vec![TernaryVal::from(i % 2 == 0), TernaryVal::False],
+                vec![TernaryVal::True],
+            );
+            pla
+        })
+        .collect();
+
+    let results = pool(4).espresso(&plas);
+
+    assert_eq!(results.len(), plas.len());
+    for (pla, result) in plas.into_iter().zip(results) {
+        assert_eq!(result.unwrap().to_string(), espresso(pla).to_string());
+    }
+}
+
+#[test]
+fn test_pool_errors() {
+    let results = pool(2).espresso(&[PlaBinary::default()]);
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].as_ref().unwrap_err(), &EspressoError::EmptyCover);
+
+    let results = EspressoPool::new(1)
+        .with_worker("does-not-exist")
+        .espresso(&[PlaBinary::default()]);
+    assert!(matches!(results[0], Err(EspressoError::WorkerFailed(_))));
+}